@@ -0,0 +1,174 @@
+#[dojo::interface]
+trait ISpawner<T> {
+    fn setup(ref world: IWorldDispatcher);
+    fn spawn_player(ref world: IWorldDispatcher, pid: felt252, start_room: felt252);
+}
+
+#[dojo::contract]
+pub mod spawner {
+    use starknet::{ContractAddress, testing, get_caller_address};
+    use core::byte_array::ByteArrayTrait;
+    use core::array::ArrayTrait;
+    use core::option::OptionTrait;
+    use super::ISpawner;
+
+    use the_oruggin_trail::models::{
+        zrk_enums as zrk, 
+        txtdef::{Txtdef}, 
+        action::{Action}, 
+        object::{Object}, 
+        room::{Room}, 
+        player::{Player},
+        inventory::{Inventory}
+    };
+
+    use the_oruggin_trail::constants::zrk_constants as zc;
+    use the_oruggin_trail::constants::zrk_constants::{roomid as rm, statusid as st};
+    use the_oruggin_trail::lib::hash_utils::hashutils as h_util;
+
+    #[abi(embed_v0)]
+    impl SpawnerImpl of ISpawner<ContractState> {
+        fn setup(ref world: IWorldDispatcher) {
+            make_rooms(world, 23);
+        }
+
+        // start_room 0 spawns the player in the start room of the world
+        fn spawn_player(ref world: IWorldDispatcher, pid: felt252, start_room: felt252) {
+            let location = if start_room == 0 { start_room_id() } else { start_room };
+            let player = Player{
+                player_id: pid,
+                player_adr: OTHER(),
+                location: location,
+                inventory: pid
+            };
+
+            let inv = Inventory {owner_id: pid, items: make_inventory(world, pid)};
+            set!(world, (inv));
+            set!(world, (player));
+        }
+    }
+
+    fn OTHER() -> ContractAddress { starknet::contract_address_const::<0x2>() }
+
+    fn store_objects(w: IWorldDispatcher, t: Array<Object>) {
+        for o in t {
+            set!(w, (o));
+        }
+    }
+
+    fn store_actions(w: IWorldDispatcher, t: Array<Action>) {
+        for o in t {
+            set!(w, (o));
+        }
+    }
+
+    fn store_places(w: IWorldDispatcher, t: Array<Room>) {
+        for o in t {
+            set!(w, (o));
+        }
+    }
+
+    fn store_txt(world: IWorldDispatcher, id: felt252, ownedBy: felt252, val: ByteArray) {
+        set!(world, (Txtdef { id: id, owner: ownedBy, text: val },));
+    }
+
+    // --------------------------------------------------------------------------------------------
+    // GENERATED
+    // --------------------------------------------------------------------------------------------
+
+    fn start_room_id() -> felt252 {
+        let place_name: ByteArray = "Plain";
+        h_util::str_hash(@place_name)
+    }
+
+    // the objects players start with, each player gets their own copies, their IDs tied to `pid`
+    fn make_inventory(w: IWorldDispatcher, pid: felt252) -> Array<felt252> {
+        // object 4118921361257443512// action 9301877412553321877
+        let mut action_4118921361257443512_9301877412553321877 = Action{
+            actionId: st::NONE, 
+            actionType: zrk::ActionType::Open,  
+            dBitTxt: "the tinder box creaks open", enabled: true, 
+            revertable: true, dBit: true, 
+            affectsActionId: 0,
+            affectedByActionId: 0
+        };
+        
+        let action_id_4118921361257443512_9301877412553321877 = core::pedersen::pedersen(h_util::action_hash(@action_4118921361257443512_9301877412553321877), pid);
+        action_4118921361257443512_9301877412553321877.actionId = action_id_4118921361257443512_9301877412553321877;
+        
+        let mut object_4118921361257443512 = Object{
+            objectId: st::SETME, 
+            objType: zrk::ObjectType::Box,
+            matType: zrk::MaterialType::Wood,
+            dirType: zrk::DirectionType::None,
+            destId: st::NONE,
+            objectActionIds: array![action_id_4118921361257443512_9301877412553321877,],
+            txtDefId: st::SETME 
+        };
+
+        let object_id_4118921361257443512 = core::pedersen::pedersen(h_util::obj_hash(@object_4118921361257443512), pid);
+        object_4118921361257443512.objectId = object_id_4118921361257443512;
+        let object_desc: ByteArray = "a tinder box";
+        let td_id_b = core::pedersen::pedersen(h_util::str_hash(@object_desc), pid);
+        object_4118921361257443512.txtDefId = td_id_b;
+
+        store_txt(w, td_id_b, object_id_4118921361257443512, object_desc);
+
+        
+        // object 12549699269465655794let mut object_12549699269465655794 = Object{
+            objectId: st::SETME, 
+            objType: zrk::ObjectType::Matches,
+            matType: zrk::MaterialType::Wood,
+            dirType: zrk::DirectionType::None,
+            destId: st::NONE,
+            objectActionIds: array![],
+            txtDefId: st::SETME 
+        };
+
+        let object_id_12549699269465655794 = core::pedersen::pedersen(h_util::obj_hash(@object_12549699269465655794), pid);
+        object_12549699269465655794.objectId = object_id_12549699269465655794;
+        let object_desc: ByteArray = "a box of matches";
+        let td_id_b = core::pedersen::pedersen(h_util::str_hash(@object_desc), pid);
+        object_12549699269465655794.txtDefId = td_id_b;
+
+        store_txt(w, td_id_b, object_id_12549699269465655794, object_desc);
+
+        
+        store_actions(w, array![action_4118921361257443512_9301877412553321877,]);
+        store_actions(w, array![]);
+        store_objects(w, array![object_12549699269465655794]);
+        store_objects(w, array![object_4118921361257443512]);
+        array![object_id_4118921361257443512,]
+    }
+
+    fn make_rooms(w: IWorldDispatcher, pl: felt252) {
+        // Plain
+        let _  = gen_room_8903762893379022354(w, pl);
+    }
+
+    
+    // 1. Plain: flat
+    fn gen_room_8903762893379022354(w: IWorldDispatcher, playerid: felt252) {
+        
+        // now store a room with all its shizzle
+        let room_desc: ByteArray = "flat";
+        let _txt_id = h_util::str_hash(@room_desc);
+        let place_name: ByteArray = "Plain";
+        let rmid = h_util::str_hash(@place_name);
+
+        let mut place = Room{
+            roomId: rmid,
+            roomType: zrk::RoomType::Plain,
+            txtDefId: _txt_id,
+            shortTxt: place_name,
+            objectIds: array![],
+            dirObjIds: array![],
+            players: array![]
+        };
+
+        // set main description text in world store
+        // for the place/area/room
+        store_txt(w, _txt_id, rmid, room_desc);
+        store_places(w, array![place]);
+        
+    }}
@@ -0,0 +1,136 @@
+//! The world, generated by pray: do not edit, change the config and build again.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Location {
+    Room(u64),
+    Container(u64),
+    Inventory,
+}
+
+#[derive(Debug)]
+pub struct Action {
+    pub id: u64,
+    pub action_type: &'static str,
+    pub enabled: bool,
+    pub revertable: bool,
+    pub text: &'static str,
+}
+
+#[derive(Debug)]
+pub struct Object {
+    pub id: u64,
+    pub object_type: &'static str,
+    pub material: &'static str,
+    pub description: &'static str,
+    pub direction: Option<&'static str>,
+    pub destination: Option<&'static str>,
+    pub takeable: bool,
+    pub hidden: bool,
+    pub location: Location,
+    pub actions: &'static [Action],
+}
+
+#[derive(Debug)]
+pub struct Npc {
+    pub id: u64,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub patrol: &'static [u64],
+}
+
+#[derive(Debug)]
+pub struct Room {
+    pub id: u64,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub room_type: &'static str,
+    pub biome: &'static str,
+    pub flags: &'static [&'static str],
+    pub objects: &'static [u64],
+    pub exits: &'static [u64],
+    pub npcs: &'static [Npc],
+}
+
+#[derive(Debug)]
+pub struct Level {
+    pub name: &'static str,
+    pub start_room: Option<u64>,
+    pub rooms: &'static [Room],
+}
+
+pub const LEVELS: &[Level] = &[
+    Level {
+        name: "test",
+        start_room: Some(8903762893379022354),
+        rooms: &[
+            Room {
+                id: 8903762893379022354,
+                name: "Plain",
+                description: "flat",
+                room_type: "Plain",
+                biome: "Prarie",
+                flags: &[],
+                objects: &[],
+                exits: &[],
+                npcs: &[
+                ],
+            },
+        ],
+    },
+];
+
+pub const OBJECTS: &[Object] = &[
+    Object {
+        id: 4118921361257443512,
+        object_type: "Box",
+        material: "Wood",
+        description: "a tinder box",
+        direction: None,
+        destination: None,
+        takeable: true,
+        hidden: false,
+        location: Location::Inventory,
+        actions: &[
+            Action {
+                id: 9301877412553321877,
+                action_type: "Open",
+                enabled: true,
+                revertable: true,
+                text: "the tinder box creaks open",
+            },
+        ],
+    },
+    Object {
+        id: 12549699269465655794,
+        object_type: "Matches",
+        material: "Wood",
+        description: "a box of matches",
+        direction: None,
+        destination: None,
+        takeable: true,
+        hidden: false,
+        location: Location::Container(4118921361257443512),
+        actions: &[
+        ],
+    },
+];
+
+/// Every text by locale then text ID, e.g. `room.42.description`
+pub const TEXTS: &[(&str, &[(&str, &str)])] = &[
+    (
+        "en",
+        &[
+            ("action.9301877412553321877.text", "the tinder box creaks open"),
+            ("object.12549699269465655794.description", "a box of matches"),
+            ("object.4118921361257443512.description", "a tinder box"),
+            ("room.8903762893379022354.description", "flat"),
+        ],
+    ),
+];
+
+/// The text `id` in `locale`
+pub fn text(locale: &str, id: &str) -> Option<&'static str> {
+    let (_, table) = TEXTS.iter().find(|(name, _)| *name == locale)?;
+    let index = table.binary_search_by_key(&id, |(key, _)| *key).ok()?;
+    Some(table[index].1)
+}
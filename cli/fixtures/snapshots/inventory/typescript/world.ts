@@ -0,0 +1,120 @@
+// The world, generated by pray: do not edit, change the config and build again.
+// IDs are bigints, most of them do not fit in a number.
+
+export type Location =
+  | { kind: "room"; id: bigint }
+  | { kind: "container"; id: bigint }
+  | { kind: "inventory" };
+
+export interface Action {
+  id: bigint;
+  type: string;
+  enabled: boolean;
+  revertable: boolean;
+  text: string;
+}
+
+export interface WorldObject {
+  id: bigint;
+  type: string;
+  material: string;
+  description: string;
+  direction: string | null;
+  destination: string | null;
+  takeable: boolean;
+  hidden: boolean;
+  location: Location;
+  actions: Action[];
+}
+
+export interface Npc {
+  id: bigint;
+  name: string;
+  description: string;
+  patrol: bigint[];
+}
+
+export interface Room {
+  id: bigint;
+  name: string;
+  description: string;
+  type: string;
+  biome: string;
+  flags: string[];
+  objects: bigint[];
+  exits: bigint[];
+  npcs: Npc[];
+}
+
+export interface Level {
+  name: string;
+  startRoom: bigint | null;
+  rooms: Room[];
+}
+
+export const LEVELS: Level[] = [
+  {
+    name: "test",
+    startRoom: 8903762893379022354n,
+    rooms: [
+      {
+        id: 8903762893379022354n,
+        name: "Plain",
+        description: "flat",
+        type: "Plain",
+        biome: "Prarie",
+        flags: [],
+        objects: [],
+        exits: [],
+        npcs: [
+        ],
+      },
+    ],
+  },
+];
+
+export const OBJECTS: WorldObject[] = [
+  {
+    id: 4118921361257443512n,
+    type: "Box",
+    material: "Wood",
+    description: "a tinder box",
+    direction: null,
+    destination: null,
+    takeable: true,
+    hidden: false,
+    location: { kind: "inventory" },
+    actions: [
+      {
+        id: 9301877412553321877n,
+        type: "Open",
+        enabled: true,
+        revertable: true,
+        text: "the tinder box creaks open",
+      },
+    ],
+  },
+  {
+    id: 12549699269465655794n,
+    type: "Matches",
+    material: "Wood",
+    description: "a box of matches",
+    direction: null,
+    destination: null,
+    takeable: true,
+    hidden: false,
+    location: { kind: "container", id: 4118921361257443512n },
+    actions: [
+    ],
+  },
+];
+
+/** Every text by locale then text ID, e.g. `TEXTS["fr"]["room.42.description"]` */
+export const TEXTS: Record<string, Record<string, string>> = {
+  "en": {
+    "action.9301877412553321877.text": "the tinder box creaks open",
+    "object.12549699269465655794.description": "a box of matches",
+    "object.4118921361257443512.description": "a tinder box",
+    "room.8903762893379022354.description": "flat"
+  }
+};
@@ -0,0 +1,308 @@
+#[dojo::interface]
+trait ISpawner<T> {
+    fn setup(ref world: IWorldDispatcher);
+    fn spawn_player(ref world: IWorldDispatcher, pid: felt252, start_room: felt252);
+}
+
+#[dojo::contract]
+pub mod spawner {
+    use starknet::{ContractAddress, testing, get_caller_address};
+    use core::byte_array::ByteArrayTrait;
+    use core::array::ArrayTrait;
+    use core::option::OptionTrait;
+    use super::ISpawner;
+
+    use the_oruggin_trail::models::{
+        zrk_enums as zrk, 
+        txtdef::{Txtdef}, 
+        action::{Action}, 
+        object::{Object}, 
+        room::{Room}, 
+        player::{Player},
+        inventory::{Inventory}
+    };
+
+    use the_oruggin_trail::constants::zrk_constants as zc;
+    use the_oruggin_trail::constants::zrk_constants::{roomid as rm, statusid as st};
+    use the_oruggin_trail::lib::hash_utils::hashutils as h_util;
+
+    #[abi(embed_v0)]
+    impl SpawnerImpl of ISpawner<ContractState> {
+        fn setup(ref world: IWorldDispatcher) {
+            make_rooms(world, 23);
+        }
+
+        // start_room 0 spawns the player in the start room of the world
+        fn spawn_player(ref world: IWorldDispatcher, pid: felt252, start_room: felt252) {
+            let location = if start_room == 0 { start_room_id() } else { start_room };
+            let player = Player{
+                player_id: pid,
+                player_adr: OTHER(),
+                location: location,
+                inventory: pid
+            };
+
+            let inv = Inventory {owner_id: pid, items: make_inventory(world, pid)};
+            set!(world, (inv));
+            set!(world, (player));
+        }
+    }
+
+    fn OTHER() -> ContractAddress { starknet::contract_address_const::<0x2>() }
+
+    fn store_objects(w: IWorldDispatcher, t: Array<Object>) {
+        for o in t {
+            set!(w, (o));
+        }
+    }
+
+    fn store_actions(w: IWorldDispatcher, t: Array<Action>) {
+        for o in t {
+            set!(w, (o));
+        }
+    }
+
+    fn store_places(w: IWorldDispatcher, t: Array<Room>) {
+        for o in t {
+            set!(w, (o));
+        }
+    }
+
+    fn store_txt(world: IWorldDispatcher, id: felt252, ownedBy: felt252, val: ByteArray) {
+        set!(world, (Txtdef { id: id, owner: ownedBy, text: val },));
+    }
+
+    // --------------------------------------------------------------------------------------------
+    // GENERATED
+    // --------------------------------------------------------------------------------------------
+
+    fn start_room_id() -> felt252 {
+        let place_name: ByteArray = "Plain";
+        h_util::str_hash(@place_name)
+    }
+
+    // the objects players start with, each player gets their own copies, their IDs tied to `pid`
+    fn make_inventory(w: IWorldDispatcher, pid: felt252) -> Array<felt252> {
+        array![]
+    }
+
+    fn make_rooms(w: IWorldDispatcher, pl: felt252) {
+        // Barn
+        let _  = gen_room_15111938330177384966(w, pl);
+        // Plain
+        let _  = gen_room_8903762893379022354(w, pl);
+    }
+
+    
+    // 1. Barn: smells of petrol
+    fn gen_room_15111938330177384966(w: IWorldDispatcher, playerid: felt252) {// object 17371320023256566807// action 6903909750943555559
+        // requires flag petrol
+        let mut action_17371320023256566807_6903909750943555559 = Action{
+            actionId: st::NONE, 
+            actionType: zrk::ActionType::Soak,  
+            dBitTxt: "the bale soaks up the petrol", enabled: true, 
+            revertable: false, dBit: true, 
+            affectsActionId: 0,
+            affectedByActionId: 0
+        };
+        
+        let action_id_17371320023256566807_6903909750943555559 = h_util::action_hash(@action_17371320023256566807_6903909750943555559);
+        action_17371320023256566807_6903909750943555559.actionId = action_id_17371320023256566807_6903909750943555559;
+        
+        // action 1798260798710947300
+        // requires holding object 12549699269465655794
+        // requires action 6903909750943555559 performed
+        let mut action_17371320023256566807_1798260798710947300 = Action{
+            actionId: st::NONE, 
+            actionType: zrk::ActionType::Burn,  
+            dBitTxt: "the bale goes up in flames", enabled: true, 
+            revertable: false, dBit: true, 
+            affectsActionId: 0,
+            affectedByActionId: 0
+        };
+        
+        let action_id_17371320023256566807_1798260798710947300 = h_util::action_hash(@action_17371320023256566807_1798260798710947300);
+        action_17371320023256566807_1798260798710947300.actionId = action_id_17371320023256566807_1798260798710947300;
+        
+        let mut object_17371320023256566807 = Object{
+            objectId: st::SETME, 
+            objType: zrk::ObjectType::Bale,
+            matType: zrk::MaterialType::Hay,
+            dirType: zrk::DirectionType::None,
+            destId: st::NONE,
+            objectActionIds: array![action_id_17371320023256566807_6903909750943555559,action_id_17371320023256566807_1798260798710947300,],
+            txtDefId: st::SETME 
+        };
+
+        let object_id_17371320023256566807 = h_util::obj_hash(@object_17371320023256566807);
+        object_17371320023256566807.objectId = object_id_17371320023256566807;
+        let object_desc: ByteArray = "a bale of hay";
+        let td_id_b = h_util::str_hash(@object_desc);
+        object_17371320023256566807.txtDefId = td_id_b;
+
+        store_txt(w, td_id_b, object_id_17371320023256566807, object_desc);
+
+        
+        // object 11693916378811382430let mut object_11693916378811382430 = Object{
+            objectId: st::SETME, 
+            objType: zrk::ObjectType::Place,
+            matType: zrk::MaterialType::Dirt,
+            dirType: zrk::DirectionType::None,
+            destId: st::NONE,
+            objectActionIds: array![],
+            txtDefId: st::SETME 
+        };
+
+        let object_id_11693916378811382430 = h_util::obj_hash(@object_11693916378811382430);
+        object_11693916378811382430.objectId = object_id_11693916378811382430;
+        let object_desc: ByteArray = "a pile of ash";
+        let td_id_b = h_util::str_hash(@object_desc);
+        object_11693916378811382430.txtDefId = td_id_b;
+
+        store_txt(w, td_id_b, object_id_11693916378811382430, object_desc);
+
+        
+        // object 6208501250999445817// action 3534990610971631781
+        // effect destroy object 17371320023256566807
+        // effect spawn object 11693916378811382430
+        // effect disable action 6903909750943555559
+        // effect lower flag petrol
+        // effect move room 8903762893379022354
+        let mut action_6208501250999445817_3534990610971631781 = Action{
+            actionId: st::NONE, 
+            actionType: zrk::ActionType::Kick,  
+            dBitTxt: "the pitchfork clatters", enabled: true, 
+            revertable: false, dBit: true, 
+            affectsActionId: 0,
+            affectedByActionId: 0
+        };
+        
+        let action_id_6208501250999445817_3534990610971631781 = h_util::action_hash(@action_6208501250999445817_3534990610971631781);
+        action_6208501250999445817_3534990610971631781.actionId = action_id_6208501250999445817_3534990610971631781;
+        
+        let mut object_6208501250999445817 = Object{
+            objectId: st::SETME, 
+            objType: zrk::ObjectType::Box,
+            matType: zrk::MaterialType::Iron,
+            dirType: zrk::DirectionType::None,
+            destId: st::NONE,
+            objectActionIds: array![action_id_6208501250999445817_3534990610971631781,],
+            txtDefId: st::SETME 
+        };
+
+        let object_id_6208501250999445817 = h_util::obj_hash(@object_6208501250999445817);
+        object_6208501250999445817.objectId = object_id_6208501250999445817;
+        let object_desc: ByteArray = "a pitchfork";
+        let td_id_b = h_util::str_hash(@object_desc);
+        object_6208501250999445817.txtDefId = td_id_b;
+
+        store_txt(w, td_id_b, object_id_6208501250999445817, object_desc);
+
+        
+        store_actions(w, array![action_17371320023256566807_6903909750943555559,action_17371320023256566807_1798260798710947300,]);
+        store_actions(w, array![]);
+        store_actions(w, array![action_6208501250999445817_3534990610971631781,]);
+        store_objects(w, array![object_17371320023256566807]);store_objects(w, array![object_11693916378811382430]);store_objects(w, array![object_6208501250999445817]);// store_objects(w, array![object_17371320023256566807,object_11693916378811382430,object_6208501250999445817,]);
+        
+        // now store a room with all its shizzle
+        let room_desc: ByteArray = "smells of petrol";
+        let _txt_id = h_util::str_hash(@room_desc);
+        let place_name: ByteArray = "Barn";
+        let rmid = h_util::str_hash(@place_name);
+
+        let mut place = Room{
+            roomId: rmid,
+            roomType: zrk::RoomType::Barn,
+            txtDefId: _txt_id,
+            shortTxt: place_name,
+            objectIds: array![object_id_17371320023256566807,object_id_6208501250999445817,],
+            dirObjIds: array![],
+            players: array![]
+        };
+
+        // set main description text in world store
+        // for the place/area/room
+        store_txt(w, _txt_id, rmid, room_desc);
+        store_places(w, array![place]);
+        // npc 638281148424009867 Eli: 2 dialogue lines, reacts to Dance, Fight, patrols 15111938330177384966, 8903762893379022354
+        
+    }
+    // 2. Plain: flat
+    fn gen_room_8903762893379022354(w: IWorldDispatcher, playerid: felt252) {// object 12549699269465655794let mut object_12549699269465655794 = Object{
+            objectId: st::SETME, 
+            objType: zrk::ObjectType::Matches,
+            matType: zrk::MaterialType::Wood,
+            dirType: zrk::DirectionType::None,
+            destId: st::NONE,
+            objectActionIds: array![],
+            txtDefId: st::SETME 
+        };
+
+        let object_id_12549699269465655794 = h_util::obj_hash(@object_12549699269465655794);
+        object_12549699269465655794.objectId = object_id_12549699269465655794;
+        let object_desc: ByteArray = "a box of matches";
+        let td_id_b = h_util::str_hash(@object_desc);
+        object_12549699269465655794.txtDefId = td_id_b;
+
+        store_txt(w, td_id_b, object_id_12549699269465655794, object_desc);
+
+        
+        // object 17786633386120019151// action 12457726031986547700
+        let mut action_17786633386120019151_12457726031986547700 = Action{
+            actionId: st::NONE, 
+            actionType: zrk::ActionType::Open,  
+            dBitTxt: "the path heads north", enabled: true, 
+            revertable: false, dBit: true, 
+            affectsActionId: 0,
+            affectedByActionId: 0
+        };
+        
+        let action_id_17786633386120019151_12457726031986547700 = h_util::action_hash(@action_17786633386120019151_12457726031986547700);
+        action_17786633386120019151_12457726031986547700.actionId = action_id_17786633386120019151_12457726031986547700;
+        
+        let destination = "barn.md";
+        let mut object_17786633386120019151 = Object{
+            objectId: st::SETME, 
+            objType: zrk::ObjectType::Path,
+            matType: zrk::MaterialType::Dirt,
+            dirType: zrk::DirectionType::North,
+            destId: h_util::str_hash(@destination),
+            objectActionIds: array![action_id_17786633386120019151_12457726031986547700,],
+            txtDefId: st::SETME 
+        };
+
+        let object_id_17786633386120019151 = h_util::obj_hash(@object_17786633386120019151);
+        object_17786633386120019151.objectId = object_id_17786633386120019151;
+        let object_desc: ByteArray = "a path north";
+        let td_id_b = h_util::str_hash(@object_desc);
+        object_17786633386120019151.txtDefId = td_id_b;
+
+        store_txt(w, td_id_b, object_id_17786633386120019151, object_desc);
+
+        
+        store_actions(w, array![]);
+        store_actions(w, array![action_17786633386120019151_12457726031986547700,]);
+        store_objects(w, array![object_12549699269465655794]);store_objects(w, array![object_17786633386120019151]);// store_objects(w, array![object_12549699269465655794,object_17786633386120019151,]);
+        
+        // now store a room with all its shizzle
+        let room_desc: ByteArray = "flat";
+        let _txt_id = h_util::str_hash(@room_desc);
+        let place_name: ByteArray = "Plain";
+        let rmid = h_util::str_hash(@place_name);
+
+        let mut place = Room{
+            roomId: rmid,
+            roomType: zrk::RoomType::Plain,
+            txtDefId: _txt_id,
+            shortTxt: place_name,
+            objectIds: array![object_id_12549699269465655794,],
+            dirObjIds: array![object_id_17786633386120019151,],
+            players: array![]
+        };
+
+        // set main description text in world store
+        // for the place/area/room
+        store_txt(w, _txt_id, rmid, room_desc);
+        store_places(w, array![place]);
+        
+    }}
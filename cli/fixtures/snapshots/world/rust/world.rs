@@ -0,0 +1,251 @@
+//! The world, generated by pray: do not edit, change the config and build again.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Location {
+    Room(u64),
+    Container(u64),
+    Inventory,
+}
+
+#[derive(Debug)]
+pub struct Action {
+    pub id: u64,
+    pub action_type: &'static str,
+    pub enabled: bool,
+    pub revertable: bool,
+    pub text: &'static str,
+}
+
+#[derive(Debug)]
+pub struct Object {
+    pub id: u64,
+    pub object_type: &'static str,
+    pub material: &'static str,
+    pub description: &'static str,
+    pub direction: Option<&'static str>,
+    pub destination: Option<&'static str>,
+    pub takeable: bool,
+    pub hidden: bool,
+    pub location: Location,
+    pub actions: &'static [Action],
+}
+
+#[derive(Debug)]
+pub struct Npc {
+    pub id: u64,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub patrol: &'static [u64],
+}
+
+#[derive(Debug)]
+pub struct Room {
+    pub id: u64,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub room_type: &'static str,
+    pub biome: &'static str,
+    pub flags: &'static [&'static str],
+    pub objects: &'static [u64],
+    pub exits: &'static [u64],
+    pub npcs: &'static [Npc],
+}
+
+#[derive(Debug)]
+pub struct Level {
+    pub name: &'static str,
+    pub start_room: Option<u64>,
+    pub rooms: &'static [Room],
+}
+
+pub const LEVELS: &[Level] = &[
+    Level {
+        name: "test",
+        start_room: Some(8903762893379022354),
+        rooms: &[
+            Room {
+                id: 15111938330177384966,
+                name: "Barn",
+                description: "smells of petrol",
+                room_type: "Barn",
+                biome: "Prarie",
+                flags: &["petrol"],
+                objects: &[17371320023256566807, 6208501250999445817],
+                exits: &[],
+                npcs: &[
+                    Npc {
+                        id: 638281148424009867,
+                        name: "Eli",
+                        description: "a farmer chewing on a straw",
+                        patrol: &[15111938330177384966, 8903762893379022354],
+                    },
+                ],
+            },
+            Room {
+                id: 8903762893379022354,
+                name: "Plain",
+                description: "flat",
+                room_type: "Plain",
+                biome: "Prarie",
+                flags: &[],
+                objects: &[12549699269465655794],
+                exits: &[17786633386120019151],
+                npcs: &[
+                ],
+            },
+        ],
+    },
+];
+
+pub const OBJECTS: &[Object] = &[
+    Object {
+        id: 17371320023256566807,
+        object_type: "Bale",
+        material: "Hay",
+        description: "a bale of hay",
+        direction: None,
+        destination: None,
+        takeable: false,
+        hidden: false,
+        location: Location::Room(15111938330177384966),
+        actions: &[
+            Action {
+                id: 6903909750943555559,
+                action_type: "Soak",
+                enabled: true,
+                revertable: false,
+                text: "the bale soaks up the petrol",
+            },
+            Action {
+                id: 1798260798710947300,
+                action_type: "Burn",
+                enabled: true,
+                revertable: false,
+                text: "the bale goes up in flames",
+            },
+        ],
+    },
+    Object {
+        id: 11693916378811382430,
+        object_type: "Place",
+        material: "Dirt",
+        description: "a pile of ash",
+        direction: None,
+        destination: None,
+        takeable: false,
+        hidden: true,
+        location: Location::Room(15111938330177384966),
+        actions: &[
+        ],
+    },
+    Object {
+        id: 6208501250999445817,
+        object_type: "Box",
+        material: "Iron",
+        description: "a pitchfork",
+        direction: None,
+        destination: None,
+        takeable: false,
+        hidden: false,
+        location: Location::Room(15111938330177384966),
+        actions: &[
+            Action {
+                id: 3534990610971631781,
+                action_type: "Kick",
+                enabled: true,
+                revertable: false,
+                text: "the pitchfork clatters",
+            },
+        ],
+    },
+    Object {
+        id: 12549699269465655794,
+        object_type: "Matches",
+        material: "Wood",
+        description: "a box of matches",
+        direction: None,
+        destination: None,
+        takeable: true,
+        hidden: false,
+        location: Location::Room(8903762893379022354),
+        actions: &[
+        ],
+    },
+    Object {
+        id: 17786633386120019151,
+        object_type: "Path",
+        material: "Dirt",
+        description: "a path north",
+        direction: Some("N"),
+        destination: Some("barn.md"),
+        takeable: false,
+        hidden: false,
+        location: Location::Room(8903762893379022354),
+        actions: &[
+            Action {
+                id: 12457726031986547700,
+                action_type: "Open",
+                enabled: true,
+                revertable: false,
+                text: "the path heads north",
+            },
+        ],
+    },
+];
+
+/// Every text by locale then text ID, e.g. `room.42.description`
+pub const TEXTS: &[(&str, &[(&str, &str)])] = &[
+    (
+        "en",
+        &[
+            ("action.12457726031986547700.text", "the path heads north"),
+            ("action.1798260798710947300.text", "the bale goes up in flames"),
+            ("action.3534990610971631781.text", "the pitchfork clatters"),
+            ("action.6903909750943555559.text", "the bale soaks up the petrol"),
+            ("line.10658020165662285927.response.0", "where am I?"),
+            ("line.10658020165662285927.response.1", "nope"),
+            ("line.10658020165662285927.text", "howdy stranger, you lost?"),
+            ("line.17853205254358752204.text", "bensons plain\nthis here is Bensons plain,\nand that's my barn"),
+            ("npc.638281148424009867.description", "a farmer chewing on a straw"),
+            ("npc.638281148424009867.reaction.Dance", "Eli looks away"),
+            ("npc.638281148424009867.reaction.Fight", "Eli swings a pitchfork at you"),
+            ("object.11693916378811382430.description", "a pile of ash"),
+            ("object.12549699269465655794.description", "a box of matches"),
+            ("object.17371320023256566807.description", "a bale of hay"),
+            ("object.17786633386120019151.description", "a path north"),
+            ("object.6208501250999445817.description", "a pitchfork"),
+            ("room.15111938330177384966.description", "smells of petrol"),
+            ("room.8903762893379022354.description", "flat"),
+        ],
+    ),
+    (
+        "fr",
+        &[
+            ("action.12457726031986547700.text", "the path heads north"),
+            ("action.1798260798710947300.text", "la botte s'enflamme"),
+            ("action.3534990610971631781.text", "la fourche tombe"),
+            ("action.6903909750943555559.text", "la botte s'imbibe d'essence"),
+            ("line.10658020165662285927.response.0", "où suis-je ?"),
+            ("line.10658020165662285927.response.1", "non"),
+            ("line.10658020165662285927.text", "salut l'étranger, perdu ?"),
+            ("line.17853205254358752204.text", "la plaine\nc'est la plaine de Benson"),
+            ("npc.638281148424009867.description", "un fermier qui mâchonne une paille"),
+            ("npc.638281148424009867.reaction.Dance", "Eli looks away"),
+            ("npc.638281148424009867.reaction.Fight", "Eli vous attaque à la fourche"),
+            ("object.11693916378811382430.description", "un tas de cendres"),
+            ("object.12549699269465655794.description", "a box of matches"),
+            ("object.17371320023256566807.description", "une botte de foin"),
+            ("object.17786633386120019151.description", "a path north"),
+            ("object.6208501250999445817.description", "une fourche"),
+            ("room.15111938330177384966.description", "sent l'essence"),
+            ("room.8903762893379022354.description", "flat"),
+        ],
+    ),
+];
+
+/// The text `id` in `locale`
+pub fn text(locale: &str, id: &str) -> Option<&'static str> {
+    let (_, table) = TEXTS.iter().find(|(name, _)| *name == locale)?;
+    let index = table.binary_search_by_key(&id, |(key, _)| *key).ok()?;
+    Some(table[index].1)
+}
@@ -0,0 +1,232 @@
+// The world, generated by pray: do not edit, change the config and build again.
+// IDs are bigints, most of them do not fit in a number.
+
+export type Location =
+  | { kind: "room"; id: bigint }
+  | { kind: "container"; id: bigint }
+  | { kind: "inventory" };
+
+export interface Action {
+  id: bigint;
+  type: string;
+  enabled: boolean;
+  revertable: boolean;
+  text: string;
+}
+
+export interface WorldObject {
+  id: bigint;
+  type: string;
+  material: string;
+  description: string;
+  direction: string | null;
+  destination: string | null;
+  takeable: boolean;
+  hidden: boolean;
+  location: Location;
+  actions: Action[];
+}
+
+export interface Npc {
+  id: bigint;
+  name: string;
+  description: string;
+  patrol: bigint[];
+}
+
+export interface Room {
+  id: bigint;
+  name: string;
+  description: string;
+  type: string;
+  biome: string;
+  flags: string[];
+  objects: bigint[];
+  exits: bigint[];
+  npcs: Npc[];
+}
+
+export interface Level {
+  name: string;
+  startRoom: bigint | null;
+  rooms: Room[];
+}
+
+export const LEVELS: Level[] = [
+  {
+    name: "test",
+    startRoom: 8903762893379022354n,
+    rooms: [
+      {
+        id: 15111938330177384966n,
+        name: "Barn",
+        description: "smells of petrol",
+        type: "Barn",
+        biome: "Prarie",
+        flags: ["petrol"],
+        objects: [17371320023256566807n, 6208501250999445817n],
+        exits: [],
+        npcs: [
+          {
+            id: 638281148424009867n,
+            name: "Eli",
+            description: "a farmer chewing on a straw",
+            patrol: [15111938330177384966n, 8903762893379022354n],
+          },
+        ],
+      },
+      {
+        id: 8903762893379022354n,
+        name: "Plain",
+        description: "flat",
+        type: "Plain",
+        biome: "Prarie",
+        flags: [],
+        objects: [12549699269465655794n],
+        exits: [17786633386120019151n],
+        npcs: [
+        ],
+      },
+    ],
+  },
+];
+
+export const OBJECTS: WorldObject[] = [
+  {
+    id: 17371320023256566807n,
+    type: "Bale",
+    material: "Hay",
+    description: "a bale of hay",
+    direction: null,
+    destination: null,
+    takeable: false,
+    hidden: false,
+    location: { kind: "room", id: 15111938330177384966n },
+    actions: [
+      {
+        id: 6903909750943555559n,
+        type: "Soak",
+        enabled: true,
+        revertable: false,
+        text: "the bale soaks up the petrol",
+      },
+      {
+        id: 1798260798710947300n,
+        type: "Burn",
+        enabled: true,
+        revertable: false,
+        text: "the bale goes up in flames",
+      },
+    ],
+  },
+  {
+    id: 11693916378811382430n,
+    type: "Place",
+    material: "Dirt",
+    description: "a pile of ash",
+    direction: null,
+    destination: null,
+    takeable: false,
+    hidden: true,
+    location: { kind: "room", id: 15111938330177384966n },
+    actions: [
+    ],
+  },
+  {
+    id: 6208501250999445817n,
+    type: "Box",
+    material: "Iron",
+    description: "a pitchfork",
+    direction: null,
+    destination: null,
+    takeable: false,
+    hidden: false,
+    location: { kind: "room", id: 15111938330177384966n },
+    actions: [
+      {
+        id: 3534990610971631781n,
+        type: "Kick",
+        enabled: true,
+        revertable: false,
+        text: "the pitchfork clatters",
+      },
+    ],
+  },
+  {
+    id: 12549699269465655794n,
+    type: "Matches",
+    material: "Wood",
+    description: "a box of matches",
+    direction: null,
+    destination: null,
+    takeable: true,
+    hidden: false,
+    location: { kind: "room", id: 8903762893379022354n },
+    actions: [
+    ],
+  },
+  {
+    id: 17786633386120019151n,
+    type: "Path",
+    material: "Dirt",
+    description: "a path north",
+    direction: "N",
+    destination: "barn.md",
+    takeable: false,
+    hidden: false,
+    location: { kind: "room", id: 8903762893379022354n },
+    actions: [
+      {
+        id: 12457726031986547700n,
+        type: "Open",
+        enabled: true,
+        revertable: false,
+        text: "the path heads north",
+      },
+    ],
+  },
+];
+
+/** Every text by locale then text ID, e.g. `TEXTS["fr"]["room.42.description"]` */
+export const TEXTS: Record<string, Record<string, string>> = {
+  "en": {
+    "action.12457726031986547700.text": "the path heads north",
+    "action.1798260798710947300.text": "the bale goes up in flames",
+    "action.3534990610971631781.text": "the pitchfork clatters",
+    "action.6903909750943555559.text": "the bale soaks up the petrol",
+    "line.10658020165662285927.response.0": "where am I?",
+    "line.10658020165662285927.response.1": "nope",
+    "line.10658020165662285927.text": "howdy stranger, you lost?",
+    "line.17853205254358752204.text": "bensons plain\nthis here is Bensons plain,\nand that's my barn",
+    "npc.638281148424009867.description": "a farmer chewing on a straw",
+    "npc.638281148424009867.reaction.Dance": "Eli looks away",
+    "npc.638281148424009867.reaction.Fight": "Eli swings a pitchfork at you",
+    "object.11693916378811382430.description": "a pile of ash",
+    "object.12549699269465655794.description": "a box of matches",
+    "object.17371320023256566807.description": "a bale of hay",
+    "object.17786633386120019151.description": "a path north",
+    "object.6208501250999445817.description": "a pitchfork",
+    "room.15111938330177384966.description": "smells of petrol",
+    "room.8903762893379022354.description": "flat"
+  },
+  "fr": {
+    "action.12457726031986547700.text": "the path heads north",
+    "action.1798260798710947300.text": "la botte s'enflamme",
+    "action.3534990610971631781.text": "la fourche tombe",
+    "action.6903909750943555559.text": "la botte s'imbibe d'essence",
+    "line.10658020165662285927.response.0": "où suis-je ?",
+    "line.10658020165662285927.response.1": "non",
+    "line.10658020165662285927.text": "salut l'étranger, perdu ?",
+    "line.17853205254358752204.text": "la plaine\nc'est la plaine de Benson",
+    "npc.638281148424009867.description": "un fermier qui mâchonne une paille",
+    "npc.638281148424009867.reaction.Dance": "Eli looks away",
+    "npc.638281148424009867.reaction.Fight": "Eli vous attaque à la fourche",
+    "object.11693916378811382430.description": "un tas de cendres",
+    "object.12549699269465655794.description": "a box of matches",
+    "object.17371320023256566807.description": "une botte de foin",
+    "object.17786633386120019151.description": "a path north",
+    "object.6208501250999445817.description": "une fourche",
+    "room.15111938330177384966.description": "sent l'essence",
+    "room.8903762893379022354.description": "flat"
+  }
+};
@@ -0,0 +1,266 @@
+//! The world, generated by pray: do not edit, change the config and build again.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Location {
+    Room(u64),
+    Container(u64),
+    Inventory,
+}
+
+#[derive(Debug)]
+pub struct Action {
+    pub id: u64,
+    pub action_type: &'static str,
+    pub enabled: bool,
+    pub revertable: bool,
+    pub text: &'static str,
+}
+
+#[derive(Debug)]
+pub struct Object {
+    pub id: u64,
+    pub object_type: &'static str,
+    pub material: &'static str,
+    pub description: &'static str,
+    pub direction: Option<&'static str>,
+    pub destination: Option<&'static str>,
+    pub takeable: bool,
+    pub hidden: bool,
+    pub location: Location,
+    pub actions: &'static [Action],
+}
+
+#[derive(Debug)]
+pub struct Npc {
+    pub id: u64,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub patrol: &'static [u64],
+}
+
+#[derive(Debug)]
+pub struct Room {
+    pub id: u64,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub room_type: &'static str,
+    pub biome: &'static str,
+    pub flags: &'static [&'static str],
+    pub objects: &'static [u64],
+    pub exits: &'static [u64],
+    pub npcs: &'static [Npc],
+}
+
+#[derive(Debug)]
+pub struct Level {
+    pub name: &'static str,
+    pub start_room: Option<u64>,
+    pub rooms: &'static [Room],
+}
+
+pub const LEVELS: &[Level] = &[
+    Level {
+        name: "test",
+        start_room: None,
+        rooms: &[
+            Room {
+                id: 11107137240536497418,
+                name: "Eli's Barn",
+                description: "the barn is old and smells of old hay and oddly dissolution\nthe floor is dirt and trampled dried horse shit scattered with straw and broken bottles\nthe smell is not unpleasent and reminds you faintly of petrol and old socks",
+                room_type: "barn",
+                biome: "Prarie",
+                flags: &[],
+                objects: &[],
+                exits: &[10414226638441273874, 4916953867006087388],
+                npcs: &[
+                ],
+            },
+            Room {
+                id: 8892581999139148090,
+                name: "Walking Eagle Pass",
+                description: "it winds through the mountains, the path is treacherous\ntoilet papered trees cover the steep\nvalley sides below you.\nOn closer inspection the TP might\nbe the remains of a cricket team\nor perhaps a lost and very dead KKK picnic group.\nIt's brass monkeys.",
+                room_type: "mountains",
+                biome: "Mountains",
+                flags: &[],
+                objects: &[],
+                exits: &[745772409139972109],
+                npcs: &[
+                ],
+            },
+            Room {
+                id: 15740072870286221930,
+                name: "Bensons plain",
+                description: "the plain reaches seemingly endlessly to the sky in all directions\nand the sky itself feels greasy and cold.\npyramidal rough shapes dot the horizin and land which\nupon closer examination are made from bufalo skulls.\nThe air tastes of grease and bensons.\nhappy happy happy",
+                room_type: "plain",
+                biome: "Prarie",
+                flags: &[],
+                objects: &[17189994194645879202],
+                exits: &[4142895348942435842, 2294365566944327029],
+                npcs: &[
+                ],
+            },
+        ],
+    },
+];
+
+pub const OBJECTS: &[Object] = &[
+    Object {
+        id: 10414226638441273874,
+        object_type: "door",
+        material: "wood",
+        description: "an old wooden barn door, leads south",
+        direction: Some("South"),
+        destination: Some("bensons-plain.md"),
+        takeable: false,
+        hidden: false,
+        location: Location::Room(11107137240536497418),
+        actions: &[
+            Action {
+                id: 4328927602301159032,
+                action_type: "open",
+                enabled: true,
+                revertable: false,
+                text: "the door, closes with a creak",
+            },
+        ],
+    },
+    Object {
+        id: 4916953867006087388,
+        object_type: "window",
+        material: "glass",
+        description: "a dusty window, at chest height",
+        direction: Some("West"),
+        destination: Some("elis-forge.md"),
+        takeable: false,
+        hidden: false,
+        location: Location::Room(11107137240536497418),
+        actions: &[
+            Action {
+                id: 14604306511083742153,
+                action_type: "open",
+                enabled: true,
+                revertable: false,
+                text: "the window, now broken, falls open",
+            },
+            Action {
+                id: 3629301349604561982,
+                action_type: "break",
+                enabled: true,
+                revertable: false,
+                text: "the window, smashes, glass flies everywhere, very very satisfying",
+            },
+        ],
+    },
+    Object {
+        id: 745772409139972109,
+        object_type: "path",
+        material: "dirt",
+        description: "path",
+        direction: Some("West"),
+        destination: Some("bensons-plain.md"),
+        takeable: false,
+        hidden: false,
+        location: Location::Room(8892581999139148090),
+        actions: &[
+            Action {
+                id: 14833044636746871315,
+                action_type: "open",
+                enabled: true,
+                revertable: false,
+                text: "the path winds west, it is open",
+            },
+        ],
+    },
+    Object {
+        id: 4142895348942435842,
+        object_type: "path",
+        material: "dirt",
+        description: "a path east leads upwards toward the mountains",
+        direction: Some("East"),
+        destination: Some("walking-eagle-pass.md"),
+        takeable: false,
+        hidden: false,
+        location: Location::Room(15740072870286221930),
+        actions: &[
+            Action {
+                id: 16668157595971844890,
+                action_type: "open",
+                enabled: true,
+                revertable: false,
+                text: "the path winds east, it is open",
+            },
+        ],
+    },
+    Object {
+        id: 2294365566944327029,
+        object_type: "path",
+        material: "dirt",
+        description: "a path north leads toward a large wooden barn",
+        direction: Some("North"),
+        destination: Some("elis-barn.md"),
+        takeable: false,
+        hidden: false,
+        location: Location::Room(15740072870286221930),
+        actions: &[
+            Action {
+                id: 13058015828559547750,
+                action_type: "open",
+                enabled: true,
+                revertable: false,
+                text: "the path heads north, it leads to a barn",
+            },
+        ],
+    },
+    Object {
+        id: 17189994194645879202,
+        object_type: "ball",
+        material: "leather",
+        description: "a knock off UEFA football\nits a bit battered and bruised\nnot exactly a sphere\nbut you can kick it",
+        direction: None,
+        destination: None,
+        takeable: false,
+        hidden: false,
+        location: Location::Room(15740072870286221930),
+        actions: &[
+            Action {
+                id: 15552978697807030543,
+                action_type: "kick",
+                enabled: true,
+                revertable: false,
+                text: "the ball bounces feebly and rolls into some dog shit. fun.",
+            },
+        ],
+    },
+];
+
+/// Every text by locale then text ID, e.g. `room.42.description`
+pub const TEXTS: &[(&str, &[(&str, &str)])] = &[
+    (
+        "en",
+        &[
+            ("action.13058015828559547750.text", "the path heads north, it leads to a barn"),
+            ("action.14604306511083742153.text", "the window, now broken, falls open"),
+            ("action.14833044636746871315.text", "the path winds west, it is open"),
+            ("action.15552978697807030543.text", "the ball bounces feebly and rolls into some dog shit. fun."),
+            ("action.16668157595971844890.text", "the path winds east, it is open"),
+            ("action.3629301349604561982.text", "the window, smashes, glass flies everywhere, very very satisfying"),
+            ("action.4328927602301159032.text", "the door, closes with a creak"),
+            ("object.10414226638441273874.description", "an old wooden barn door, leads south"),
+            ("object.17189994194645879202.description", "a knock off UEFA football\nits a bit battered and bruised\nnot exactly a sphere\nbut you can kick it"),
+            ("object.2294365566944327029.description", "a path north leads toward a large wooden barn"),
+            ("object.4142895348942435842.description", "a path east leads upwards toward the mountains"),
+            ("object.4916953867006087388.description", "a dusty window, at chest height"),
+            ("object.745772409139972109.description", "path"),
+            ("room.11107137240536497418.description", "the barn is old and smells of old hay and oddly dissolution\nthe floor is dirt and trampled dried horse shit scattered with straw and broken bottles\nthe smell is not unpleasent and reminds you faintly of petrol and old socks"),
+            ("room.15740072870286221930.description", "the plain reaches seemingly endlessly to the sky in all directions\nand the sky itself feels greasy and cold.\npyramidal rough shapes dot the horizin and land which\nupon closer examination are made from bufalo skulls.\nThe air tastes of grease and bensons.\nhappy happy happy"),
+            ("room.8892581999139148090.description", "it winds through the mountains, the path is treacherous\ntoilet papered trees cover the steep\nvalley sides below you.\nOn closer inspection the TP might\nbe the remains of a cricket team\nor perhaps a lost and very dead KKK picnic group.\nIt's brass monkeys."),
+        ],
+    ),
+];
+
+/// The text `id` in `locale`
+pub fn text(locale: &str, id: &str) -> Option<&'static str> {
+    let (_, table) = TEXTS.iter().find(|(name, _)| *name == locale)?;
+    let index = table.binary_search_by_key(&id, |(key, _)| *key).ok()?;
+    Some(table[index].1)
+}
@@ -0,0 +1,250 @@
+// The world, generated by pray: do not edit, change the config and build again.
+// IDs are bigints, most of them do not fit in a number.
+
+export type Location =
+  | { kind: "room"; id: bigint }
+  | { kind: "container"; id: bigint }
+  | { kind: "inventory" };
+
+export interface Action {
+  id: bigint;
+  type: string;
+  enabled: boolean;
+  revertable: boolean;
+  text: string;
+}
+
+export interface WorldObject {
+  id: bigint;
+  type: string;
+  material: string;
+  description: string;
+  direction: string | null;
+  destination: string | null;
+  takeable: boolean;
+  hidden: boolean;
+  location: Location;
+  actions: Action[];
+}
+
+export interface Npc {
+  id: bigint;
+  name: string;
+  description: string;
+  patrol: bigint[];
+}
+
+export interface Room {
+  id: bigint;
+  name: string;
+  description: string;
+  type: string;
+  biome: string;
+  flags: string[];
+  objects: bigint[];
+  exits: bigint[];
+  npcs: Npc[];
+}
+
+export interface Level {
+  name: string;
+  startRoom: bigint | null;
+  rooms: Room[];
+}
+
+export const LEVELS: Level[] = [
+  {
+    name: "test",
+    startRoom: null,
+    rooms: [
+      {
+        id: 11107137240536497418n,
+        name: "Eli's Barn",
+        description: "the barn is old and smells of old hay and oddly dissolution\nthe floor is dirt and trampled dried horse shit scattered with straw and broken bottles\nthe smell is not unpleasent and reminds you faintly of petrol and old socks",
+        type: "barn",
+        biome: "Prarie",
+        flags: [],
+        objects: [],
+        exits: [10414226638441273874n, 4916953867006087388n],
+        npcs: [
+        ],
+      },
+      {
+        id: 8892581999139148090n,
+        name: "Walking Eagle Pass",
+        description: "it winds through the mountains, the path is treacherous\ntoilet papered trees cover the steep\nvalley sides below you.\nOn closer inspection the TP might\nbe the remains of a cricket team\nor perhaps a lost and very dead KKK picnic group.\nIt's brass monkeys.",
+        type: "mountains",
+        biome: "Mountains",
+        flags: [],
+        objects: [],
+        exits: [745772409139972109n],
+        npcs: [
+        ],
+      },
+      {
+        id: 15740072870286221930n,
+        name: "Bensons plain",
+        description: "the plain reaches seemingly endlessly to the sky in all directions\nand the sky itself feels greasy and cold.\npyramidal rough shapes dot the horizin and land which\nupon closer examination are made from bufalo skulls.\nThe air tastes of grease and bensons.\nhappy happy happy",
+        type: "plain",
+        biome: "Prarie",
+        flags: [],
+        objects: [17189994194645879202n],
+        exits: [4142895348942435842n, 2294365566944327029n],
+        npcs: [
+        ],
+      },
+    ],
+  },
+];
+
+export const OBJECTS: WorldObject[] = [
+  {
+    id: 10414226638441273874n,
+    type: "door",
+    material: "wood",
+    description: "an old wooden barn door, leads south",
+    direction: "South",
+    destination: "bensons-plain.md",
+    takeable: false,
+    hidden: false,
+    location: { kind: "room", id: 11107137240536497418n },
+    actions: [
+      {
+        id: 4328927602301159032n,
+        type: "open",
+        enabled: true,
+        revertable: false,
+        text: "the door, closes with a creak",
+      },
+    ],
+  },
+  {
+    id: 4916953867006087388n,
+    type: "window",
+    material: "glass",
+    description: "a dusty window, at chest height",
+    direction: "West",
+    destination: "elis-forge.md",
+    takeable: false,
+    hidden: false,
+    location: { kind: "room", id: 11107137240536497418n },
+    actions: [
+      {
+        id: 14604306511083742153n,
+        type: "open",
+        enabled: true,
+        revertable: false,
+        text: "the window, now broken, falls open",
+      },
+      {
+        id: 3629301349604561982n,
+        type: "break",
+        enabled: true,
+        revertable: false,
+        text: "the window, smashes, glass flies everywhere, very very satisfying",
+      },
+    ],
+  },
+  {
+    id: 745772409139972109n,
+    type: "path",
+    material: "dirt",
+    description: "path",
+    direction: "West",
+    destination: "bensons-plain.md",
+    takeable: false,
+    hidden: false,
+    location: { kind: "room", id: 8892581999139148090n },
+    actions: [
+      {
+        id: 14833044636746871315n,
+        type: "open",
+        enabled: true,
+        revertable: false,
+        text: "the path winds west, it is open",
+      },
+    ],
+  },
+  {
+    id: 4142895348942435842n,
+    type: "path",
+    material: "dirt",
+    description: "a path east leads upwards toward the mountains",
+    direction: "East",
+    destination: "walking-eagle-pass.md",
+    takeable: false,
+    hidden: false,
+    location: { kind: "room", id: 15740072870286221930n },
+    actions: [
+      {
+        id: 16668157595971844890n,
+        type: "open",
+        enabled: true,
+        revertable: false,
+        text: "the path winds east, it is open",
+      },
+    ],
+  },
+  {
+    id: 2294365566944327029n,
+    type: "path",
+    material: "dirt",
+    description: "a path north leads toward a large wooden barn",
+    direction: "North",
+    destination: "elis-barn.md",
+    takeable: false,
+    hidden: false,
+    location: { kind: "room", id: 15740072870286221930n },
+    actions: [
+      {
+        id: 13058015828559547750n,
+        type: "open",
+        enabled: true,
+        revertable: false,
+        text: "the path heads north, it leads to a barn",
+      },
+    ],
+  },
+  {
+    id: 17189994194645879202n,
+    type: "ball",
+    material: "leather",
+    description: "a knock off UEFA football\nits a bit battered and bruised\nnot exactly a sphere\nbut you can kick it",
+    direction: null,
+    destination: null,
+    takeable: false,
+    hidden: false,
+    location: { kind: "room", id: 15740072870286221930n },
+    actions: [
+      {
+        id: 15552978697807030543n,
+        type: "kick",
+        enabled: true,
+        revertable: false,
+        text: "the ball bounces feebly and rolls into some dog shit. fun.",
+      },
+    ],
+  },
+];
+
+/** Every text by locale then text ID, e.g. `TEXTS["fr"]["room.42.description"]` */
+export const TEXTS: Record<string, Record<string, string>> = {
+  "en": {
+    "action.13058015828559547750.text": "the path heads north, it leads to a barn",
+    "action.14604306511083742153.text": "the window, now broken, falls open",
+    "action.14833044636746871315.text": "the path winds west, it is open",
+    "action.15552978697807030543.text": "the ball bounces feebly and rolls into some dog shit. fun.",
+    "action.16668157595971844890.text": "the path winds east, it is open",
+    "action.3629301349604561982.text": "the window, smashes, glass flies everywhere, very very satisfying",
+    "action.4328927602301159032.text": "the door, closes with a creak",
+    "object.10414226638441273874.description": "an old wooden barn door, leads south",
+    "object.17189994194645879202.description": "a knock off UEFA football\nits a bit battered and bruised\nnot exactly a sphere\nbut you can kick it",
+    "object.2294365566944327029.description": "a path north leads toward a large wooden barn",
+    "object.4142895348942435842.description": "a path east leads upwards toward the mountains",
+    "object.4916953867006087388.description": "a dusty window, at chest height",
+    "object.745772409139972109.description": "path",
+    "room.11107137240536497418.description": "the barn is old and smells of old hay and oddly dissolution\nthe floor is dirt and trampled dried horse shit scattered with straw and broken bottles\nthe smell is not unpleasent and reminds you faintly of petrol and old socks",
+    "room.15740072870286221930.description": "the plain reaches seemingly endlessly to the sky in all directions\nand the sky itself feels greasy and cold.\npyramidal rough shapes dot the horizin and land which\nupon closer examination are made from bufalo skulls.\nThe air tastes of grease and bensons.\nhappy happy happy",
+    "room.8892581999139148090.description": "it winds through the mountains, the path is treacherous\ntoilet papered trees cover the steep\nvalley sides below you.\nOn closer inspection the TP might\nbe the remains of a cricket team\nor perhaps a lost and very dead KKK picnic group.\nIt's brass monkeys."
+  }
+};
@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+
+/// Options that take a value, e.g. `--target spawner` or `--target=spawner`
+const OPTIONS: &[&str] = &[
+    "--target",
+    "--manifest",
+    "--defaults",
+    "--set",
+    "--templates",
+    "--max-text-bytes",
+    "--max-writes",
+    "--max-felts",
+    "--max-rooms",
+];
+
+/// Command line split into positional values, `--flag`s and `--option value` pairs
+pub struct Args {
+    positional: Vec<String>,
+    flags: Vec<String>,
+    options: HashMap<String, Vec<String>>,
+}
+
+impl Args {
+    pub fn parse(args: impl Iterator<Item = String>) -> Args {
+        let mut parsed = Args {
+            positional: Vec::new(),
+            flags: Vec::new(),
+            options: HashMap::new(),
+        };
+
+        let mut args = args.peekable();
+        while let Some(arg) = args.next() {
+            if let Some((name, value)) = arg.split_once('=').filter(|_| arg.starts_with("--")) {
+                parsed
+                    .options
+                    .entry(name.to_string())
+                    .or_default()
+                    .push(value.to_string());
+            } else if OPTIONS.contains(&arg.as_str()) {
+                let value = args.next().unwrap_or_default();
+                parsed.options.entry(arg).or_default().push(value);
+            } else if arg.starts_with("--") {
+                parsed.flags.push(arg);
+            } else {
+                parsed.positional.push(arg);
+            }
+        }
+        parsed
+    }
+
+    /// Remove and return the first positional value when it is one of `commands`
+    pub fn command(&mut self, commands: &[&str]) -> Option<String> {
+        let first = self.positional.first()?;
+        if commands.contains(&first.as_str()) {
+            Some(self.positional.remove(0))
+        } else {
+            None
+        }
+    }
+
+    pub fn positional(&self) -> &[String] {
+        &self.positional
+    }
+
+    pub fn flag(&self, name: &str) -> bool {
+        self.flags.iter().any(|flag| flag == name)
+    }
+
+    /// The first flag or option given that is not in `known`, e.g. a typo
+    pub fn unknown(&self, known: &[&str]) -> Option<&str> {
+        let mut options: Vec<&String> = self.options.keys().collect();
+        options.sort();
+        self.flags
+            .iter()
+            .chain(options)
+            .map(String::as_str)
+            .find(|name| !known.contains(name))
+    }
+
+    /// The value of an option, the last one when it is given several times
+    pub fn option(&self, name: &str) -> Option<&str> {
+        self.values(name).last().copied()
+    }
+
+    /// Every value of an option given several times, e.g. `--templates a --templates b`
+    pub fn values(&self, name: &str) -> Vec<&str> {
+        self.options
+            .get(name)
+            .map(|values| values.iter().map(String::as_str).collect())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Args {
+        Args::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn finds_unknown_flags_and_options() {
+        let known = ["--check", "--target"];
+        assert_eq!(
+            parse(&["build", "--check", "--target", "x"]).unknown(&known),
+            None
+        );
+        assert_eq!(parse(&["build", "--chek"]).unknown(&known), Some("--chek"));
+        assert_eq!(
+            parse(&["build", "--tagret=x"]).unknown(&known),
+            Some("--tagret")
+        );
+    }
+}
@@ -0,0 +1,77 @@
+//! Template sets shipped inside the binary, picked by name instead of a templates folder.
+//! They are rendered with the same context as any other template.
+
+/// Templates of a set by file name, as they would be named in a templates folder
+pub struct Set {
+    pub name: &'static str,
+    /// What the set generates
+    pub description: &'static str,
+    pub templates: &'static [(&'static str, &'static str)],
+}
+
+/// Macros every template can import, e.g. `{% import "std/zrk.tera" as zrk %}`.
+/// A template folder can override them by name like any other template.
+pub const STD: &[(&str, &str)] = &[
+    ("std/describe.tera", include_str!("../std/describe.tera")),
+    ("std/zrk.tera", include_str!("../std/zrk.tera")),
+];
+
+/// Set `pray init` ejects when none is named
+pub const DEFAULT: &str = "cairo";
+
+pub const SETS: &[Set] = &[
+    Set {
+        name: "cairo",
+        description: "the spawner contract of The Oruggin Trail",
+        templates: &[(
+            "spawner.cairo.tera",
+            include_str!("../templates/spawner.cairo.tera"),
+        )],
+    },
+    Set {
+        name: "json",
+        description: "a JSON bundle of the world, its objects, texts and batches, for indexers",
+        templates: &[(
+            "world.json.tera",
+            include_str!("../builtin/json/world.json.tera"),
+        )],
+    },
+    Set {
+        name: "rust",
+        description: "Rust structs and constants of the world, for bots",
+        templates: &[(
+            "world.rs.tera",
+            include_str!("../builtin/rust/world.rs.tera"),
+        )],
+    },
+    Set {
+        name: "typescript",
+        description: "TypeScript types and constants of the world, for web clients",
+        templates: &[(
+            "world.ts.tera",
+            include_str!("../builtin/typescript/world.ts.tera"),
+        )],
+    },
+];
+
+pub fn find(name: &str) -> Option<&'static Set> {
+    SETS.iter().find(|set| set.name == name)
+}
+
+/// Source of a shipped template by name, from the standard macros or any set
+pub fn source(name: &str) -> Option<&'static str> {
+    let sets = SETS.iter().flat_map(|set| set.templates.iter());
+    STD.iter()
+        .chain(sets)
+        .find(|(template, _)| *template == name)
+        .map(|(_, source)| *source)
+}
+
+/// Every set with its description, one per line, e.g. for error messages
+pub fn list() -> String {
+    let lines: Vec<String> = SETS
+        .iter()
+        .map(|set| format!("  {}: {}", set.name, set.description))
+        .collect();
+    lines.join("\n")
+}
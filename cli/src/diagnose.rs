@@ -0,0 +1,592 @@
+//! Explaining why a template failed to render: where in the template, which expression,
+//! and which room, object or action the loops and macros were on.
+//!
+//! Tera only tells what went wrong, so the expression is found back in the template source,
+//! and the templates are rendered again with every loop and macro recording what it is on.
+
+use crate::builtin;
+use crate::render::register_filters;
+use std::collections::HashMap;
+use std::error::Error as _;
+use std::fmt;
+use std::fs;
+use std::sync::{Arc, Mutex};
+use tera::{Context, ErrorKind, Tera, Value};
+
+const ENTER: &str = "__pray_enter";
+const LEAVE: &str = "__pray_leave";
+
+/// Where the failing expression is in the template source
+#[derive(Debug)]
+pub struct Location {
+    pub template: String,
+    /// Starting at 1
+    pub line: usize,
+    pub source: String,
+    /// How many other lines use the expression too, tera does not tell which one failed
+    pub others: usize,
+}
+
+/// A template that failed to render
+#[derive(Debug)]
+pub struct Failure {
+    /// The template and what it was rendered for, e.g. `rooms.cairo.tera for room Barn`
+    pub instance: String,
+    pub expression: Option<String>,
+    pub location: Option<Location>,
+    /// What the loops and macros were on, outermost first, e.g. `room Barn (1234)`
+    pub items: Vec<String>,
+    pub error: tera::Error,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to render {}", self.instance)?;
+        if let Some(location) = &self.location {
+            let number = location.line.to_string();
+            let margin = " ".repeat(number.len());
+            write!(f, "\n  --> {}:{}", location.template, location.line)?;
+            if location.others > 0 {
+                write!(
+                    f,
+                    " (best guess, {} other {} the expression too)",
+                    location.others,
+                    if location.others == 1 {
+                        "line uses"
+                    } else {
+                        "lines use"
+                    }
+                )?;
+            }
+            write!(f, "\n  {} |", margin)?;
+            write!(f, "\n  {} | {}", number, location.source.trim_end())?;
+            write!(f, "\n  {} |", margin)?;
+        }
+        if let Some(expression) = &self.expression {
+            write!(f, "\n  expression: {}", expression)?;
+        }
+        if !self.items.is_empty() {
+            write!(f, "\n  while rendering: {}", self.items.join(", "))?;
+        }
+        write!(f, "\n  error: {}", self.error)?;
+        let mut source = self.error.source();
+        while let Some(cause) = source {
+            write!(f, "\n  caused by: {}", cause)?;
+            source = cause.source();
+        }
+        Ok(())
+    }
+}
+
+/// Source of a loaded template, from its file or the templates shipped with pray
+fn source(tera: &Tera, name: &str) -> Option<String> {
+    let template = tera.get_template(name).ok()?;
+    match &template.path {
+        Some(path) => fs::read_to_string(path).ok(),
+        None => builtin::source(name).map(str::to_string),
+    }
+}
+
+/// The messages of the error and of its causes, innermost last, with the tera errors
+fn chain(error: &tera::Error) -> Vec<(String, Option<&tera::Error>)> {
+    let mut messages = vec![(error.to_string(), Some(error))];
+    let mut source = error.source();
+    while let Some(cause) = source {
+        messages.push((cause.to_string(), cause.downcast_ref::<tera::Error>()));
+        source = cause.source();
+    }
+    messages
+}
+
+/// The text between the first pair of `open` and `close` after `prefix`
+fn quoted<'a>(message: &'a str, prefix: &str, open: char, close: char) -> Option<&'a str> {
+    let start = message.find(prefix)? + prefix.len();
+    let rest = message[start..].strip_prefix(open)?;
+    rest.split(close).next()
+}
+
+/// The expression that failed: a variable, or the filter, function or test called
+fn expression(messages: &[(String, Option<&tera::Error>)]) -> Option<String> {
+    for (message, error) in messages.iter().rev() {
+        match error.map(|error| &error.kind) {
+            Some(ErrorKind::CallFilter(name))
+            | Some(ErrorKind::CallFunction(name))
+            | Some(ErrorKind::CallTest(name)) => return Some(name.clone()),
+            _ if message.starts_with("Failed to render") => {}
+            _ => {
+                if let Some(expression) = message
+                    .split('`')
+                    .nth(1)
+                    .filter(|expression| !expression.is_empty())
+                {
+                    return Some(expression.to_string());
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Whether `expression` is in `line` on its own, not as part of a longer name
+fn contains(line: &str, expression: &str) -> bool {
+    let is_name = |c: char| c.is_alphanumeric() || c == '_' || c == '.';
+    line.match_indices(expression).any(|(start, _)| {
+        let before = line[..start].chars().next_back();
+        let after = line[start + expression.len()..].chars().next();
+        !before.is_some_and(is_name) && !after.is_some_and(is_name)
+    })
+}
+
+/// The lines of the body of the macro `name` in `source`, all of them when it is not defined there
+fn macro_body(source: &str, name: Option<&str>) -> Option<(usize, usize)> {
+    let lines: Vec<&str> = source.lines().collect();
+    let Some(name) = name else {
+        return Some((0, lines.len()));
+    };
+    let definition = format!("macro {}(", name);
+    let start = lines.iter().position(|line| line.contains(&definition))?;
+    let end = lines[start..]
+        .iter()
+        .position(|line| line.contains("endmacro"))
+        .map_or(lines.len(), |end| start + end + 1);
+    Some((start, end))
+}
+
+/// Find the failing expression in the template it failed in
+fn locate(
+    tera: &Tera,
+    file: &str,
+    messages: &[(String, Option<&tera::Error>)],
+    expression: &str,
+) -> Option<Location> {
+    let outer = &messages[0].0;
+    // `Failed to render 'a': error while rendering macro `ns::name``
+    let in_macro = quoted(outer, "error while rendering macro ", '`', '`')
+        .and_then(|call| call.split("::").last());
+    // `(error happened in 'base.tera')`
+    let in_parent = quoted(outer, "error happened in ", '\'', '\'');
+
+    let mut candidates: Vec<String> = Vec::new();
+    if in_macro.is_some() {
+        // the macro can come from any template
+        candidates.extend(tera.get_template_names().map(str::to_string));
+        candidates.sort();
+    }
+    candidates.extend(in_parent.map(str::to_string));
+    candidates.push(file.to_string());
+    if let Ok(template) = tera.get_template(file) {
+        candidates.extend(template.parents.iter().cloned());
+    }
+
+    for template in candidates {
+        let Some(source) = source(tera, &template) else {
+            continue;
+        };
+        let Some((start, end)) = macro_body(&source, in_macro) else {
+            continue;
+        };
+        let mut found = source
+            .lines()
+            .enumerate()
+            .take(end)
+            .skip(start)
+            .filter(|(_, line)| contains(line, expression));
+        if let Some((index, line)) = found.next() {
+            return Some(Location {
+                template,
+                line: index + 1,
+                source: line.to_string(),
+                others: found.count(),
+            });
+        }
+    }
+    None
+}
+
+/// A loop item or macro argument in words, by what kind of data it looks like
+fn describe(name: &str, value: &Value) -> String {
+    let text = |key: &str| match value.get(key) {
+        Some(Value::String(text)) => text.clone(),
+        Some(value) => value.to_string(),
+        None => String::new(),
+    };
+    let kinds = [
+        ("roomID", "room", "roomName"),
+        ("objID", "object", "objDescription"),
+        ("actionID", "action", "type"),
+        ("npcID", "npc", "name"),
+        ("lineID", "line", "text"),
+    ];
+    for (id, kind, label) in kinds {
+        if value.get(id).is_some() {
+            return format!("{} {} ({})", kind, text(label), text(id));
+        }
+    }
+    if value.get("levelName").is_some() {
+        return format!("level {}", text("levelName"));
+    }
+    if value.get("roomIDs").is_some() {
+        return format!("batch {}", text("name"));
+    }
+    let mut value = value.to_string();
+    if value.chars().count() > 60 {
+        value = value.chars().take(57).collect::<String>() + "...";
+    }
+    format!("{} = {}", name, value)
+}
+
+/// The names a `{% for %}` tag binds, the value last
+fn loop_names(tag: &str) -> Vec<String> {
+    let Some((names, _)) = tag
+        .strip_prefix("for ")
+        .and_then(|rest| rest.split_once(" in "))
+    else {
+        return Vec::new();
+    };
+    names
+        .split(',')
+        .map(|name| name.trim().to_string())
+        .collect()
+}
+
+/// The parameters of a `{% macro %}` tag
+fn macro_params(tag: &str) -> Vec<String> {
+    let Some(params) = tag
+        .strip_prefix("macro ")
+        .and_then(|rest| rest.split_once('('))
+        .and_then(|(_, params)| params.rsplit_once(')'))
+        .map(|(params, _)| params)
+    else {
+        return Vec::new();
+    };
+    params
+        .split(',')
+        .filter_map(|param| param.split('=').next())
+        .map(|param| param.trim().to_string())
+        .filter(|param| !param.is_empty())
+        .collect()
+}
+
+/// Record the item of every loop and the arguments of every macro when they start,
+/// and forget them when they end
+fn trace_source(source: &str) -> String {
+    let enter = |name: &str| format!("{{{{ {}(name=\"{}\", value={}) }}}}", ENTER, name, name);
+    let leave = |name: &str| format!("{{{{ {}(name=\"{}\") }}}}", LEAVE, name);
+
+    let mut traced = String::with_capacity(source.len());
+    let mut loops: Vec<String> = Vec::new();
+    let mut params: Vec<String> = Vec::new();
+    let mut rest = source;
+    while let Some(start) = rest.find("{%") {
+        let Some(length) = rest[start..].find("%}") else {
+            break;
+        };
+        let end = start + length + 2;
+        let tag = rest[start + 2..end - 2]
+            .trim_matches('-')
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        traced.push_str(&rest[..start]);
+        if tag == "raw" {
+            // nothing is a tag until the end of the raw block
+            let raw_end = rest[end..]
+                .find("endraw")
+                .and_then(|at| {
+                    rest[end + at..]
+                        .find("%}")
+                        .map(|close| end + at + close + 2)
+                })
+                .unwrap_or(rest.len());
+            traced.push_str(&rest[start..raw_end]);
+            rest = &rest[raw_end..];
+            continue;
+        }
+        if tag == "endfor" {
+            traced.extend(loops.pop().as_deref().map(leave));
+        }
+        if tag.starts_with("endmacro") {
+            traced.extend(params.drain(..).map(|param| leave(&param)));
+        }
+        traced.push_str(&rest[start..end]);
+        if tag.starts_with("for ") {
+            let names = loop_names(&tag);
+            if let Some(name) = names.last() {
+                traced.push_str(&enter(name));
+                loops.push(name.clone());
+            }
+        }
+        if tag.starts_with("macro ") {
+            params = macro_params(&tag);
+            for param in &params {
+                traced.push_str(&enter(param));
+            }
+        }
+        rest = &rest[end..];
+    }
+    traced.push_str(rest);
+    traced
+}
+
+/// What the loops and macros were on when rendering `file` fails again, outermost first
+fn trace(tera: &Tera, file: &str, context: &Context) -> Vec<String> {
+    let mut sources = Vec::new();
+    for name in tera.get_template_names() {
+        match source(tera, name) {
+            Some(source) => sources.push((name.to_string(), trace_source(&source))),
+            None => return Vec::new(),
+        }
+    }
+    let mut traced = Tera::default();
+    if traced.add_raw_templates(sources).is_err() {
+        return Vec::new();
+    }
+    register_filters(&mut traced);
+
+    let stack: Arc<Mutex<Vec<(String, Value)>>> = Arc::default();
+    let entered = Arc::clone(&stack);
+    traced.register_function(ENTER, move |args: &HashMap<String, Value>| {
+        let name = args.get("name").and_then(Value::as_str).unwrap_or_default();
+        let value = args.get("value").cloned().unwrap_or_default();
+        let mut stack = entered.lock().expect("poisoned trace");
+        // a loop left with `break` never said so
+        if let Some(index) = stack.iter().position(|(entry, _)| entry == name) {
+            stack.truncate(index);
+        }
+        stack.push((name.to_string(), value));
+        Ok(Value::String(String::new()))
+    });
+    let left = Arc::clone(&stack);
+    traced.register_function(LEAVE, move |args: &HashMap<String, Value>| {
+        let name = args.get("name").and_then(Value::as_str).unwrap_or_default();
+        let mut stack = left.lock().expect("poisoned trace");
+        if let Some(index) = stack.iter().rposition(|(entry, _)| entry == name) {
+            stack.truncate(index);
+        }
+        Ok(Value::String(String::new()))
+    });
+
+    if traced.render(file, context).is_ok() {
+        // rendering did not fail the same way, the trace means nothing
+        return Vec::new();
+    }
+    let stack = stack.lock().expect("poisoned trace");
+    let mut items: Vec<String> = stack
+        .iter()
+        .map(|(name, value)| describe(name, value))
+        .collect();
+    // a macro called with the item of the loop around it
+    items.dedup();
+    items
+}
+
+/// Explain why rendering `file` with `context` failed
+pub fn explain(
+    tera: &Tera,
+    file: &str,
+    instance: &str,
+    context: &Context,
+    error: tera::Error,
+) -> Failure {
+    let messages = chain(&error);
+    let expression = expression(&messages);
+    let location = expression
+        .as_deref()
+        .and_then(|expression| locate(tera, file, &messages, expression));
+    let items = trace(tera, file, context);
+    Failure {
+        instance: instance.to_string(),
+        expression,
+        location,
+        items,
+        error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::path::PathBuf;
+
+    /// Load `templates` from files in a fresh folder, so their source can be read back
+    fn templates(name: &str, templates: &[(&str, &str)]) -> (PathBuf, Tera) {
+        let root =
+            std::env::temp_dir().join(format!("pray-diagnose-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        let mut paths = Vec::new();
+        for (file, content) in templates {
+            fs::write(root.join(file), content).unwrap();
+            paths.push((root.join(file), Some(file.to_string())));
+        }
+        let mut tera = Tera::default();
+        tera.add_template_files(paths).unwrap();
+        register_filters(&mut tera);
+        (root, tera)
+    }
+
+    fn rooms() -> Context {
+        let mut context = Context::new();
+        context.insert(
+            "rooms",
+            &json!([
+                {"roomID": 1, "roomName": "Barn", "objects": []},
+                {"roomID": 2, "roomName": "Loft", "objects": [{"objID": 3, "objDescription": "a hatch"}]},
+            ]),
+        );
+        context
+    }
+
+    #[test]
+    fn explains_undefined_variables_in_loops() {
+        let source = "{% for room in rooms %}\n{% for object in room.objects %}\n{{ object.nme }}\n{% endfor %}\n{% endfor %}\n";
+        let (root, tera) = templates("loop", &[("rooms.txt.tera", source)]);
+        let error = tera.render("rooms.txt.tera", &rooms()).unwrap_err();
+        let failure = explain(&tera, "rooms.txt.tera", "rooms.txt.tera", &rooms(), error);
+        fs::remove_dir_all(root).unwrap();
+
+        assert_eq!(failure.expression.as_deref(), Some("object.nme"));
+        let location = failure.location.as_ref().unwrap();
+        assert_eq!(location.template, "rooms.txt.tera");
+        assert_eq!(location.line, 3);
+        assert_eq!(location.others, 0);
+        assert_eq!(failure.items, ["room Loft (2)", "object a hatch (3)"]);
+        assert!(failure.to_string().contains("\n  3 | {{ object.nme }}\n"));
+    }
+
+    #[test]
+    fn explains_undefined_variables_in_macros() {
+        let macros =
+            "{% macro name(room) %}\n{{ room.roomName }}\n{{ room.title }}\n{% endmacro name %}\n";
+        let source = "{% import \"_macros.tera\" as macros %}\n{% for room in rooms %}{{ macros::name(room=room) }}{% endfor %}\n";
+        let (root, tera) = templates(
+            "macro",
+            &[("_macros.tera", macros), ("rooms.txt.tera", source)],
+        );
+        let error = tera.render("rooms.txt.tera", &rooms()).unwrap_err();
+        let failure = explain(&tera, "rooms.txt.tera", "rooms.txt.tera", &rooms(), error);
+        fs::remove_dir_all(root).unwrap();
+
+        assert_eq!(failure.expression.as_deref(), Some("room.title"));
+        let location = failure.location.as_ref().unwrap();
+        assert_eq!(location.template, "_macros.tera");
+        assert_eq!(location.line, 3);
+        // the loop item and the macro argument are the same room
+        assert_eq!(failure.items, ["room Barn (1)"]);
+    }
+
+    #[test]
+    fn says_when_the_line_is_a_guess() {
+        let source =
+            "{% for room in rooms %}\n{{ room.title }}\n{{ room.title | upper }}\n{% endfor %}\n";
+        let (root, tera) = templates("guess", &[("rooms.txt.tera", source)]);
+        let error = tera.render("rooms.txt.tera", &rooms()).unwrap_err();
+        let failure = explain(&tera, "rooms.txt.tera", "rooms.txt.tera", &rooms(), error);
+        fs::remove_dir_all(root).unwrap();
+
+        let location = failure.location.as_ref().unwrap();
+        assert_eq!((location.line, location.others), (2, 1));
+        assert!(failure
+            .to_string()
+            .contains("rooms.txt.tera:2 (best guess, 1 other line uses the expression too)"));
+    }
+
+    #[test]
+    fn finds_quoted_text() {
+        let message = "Failed to render 'a': error while rendering macro `macros::name` (error happened in 'base.tera')";
+        assert_eq!(
+            quoted(message, "error while rendering macro ", '`', '`'),
+            Some("macros::name")
+        );
+        assert_eq!(
+            quoted(message, "error happened in ", '\'', '\''),
+            Some("base.tera")
+        );
+        assert_eq!(quoted(message, "missing ", '\'', '\''), None);
+    }
+
+    #[test]
+    fn finds_the_failing_expression() {
+        let error = tera::Error::chain(
+            "Failed to render 'a'",
+            tera::Error::msg("Variable `room.title` not found in context while rendering 'a'"),
+        );
+        assert_eq!(expression(&chain(&error)).as_deref(), Some("room.title"));
+
+        let error = tera::Error::chain(
+            "Failed to render 'a'",
+            tera::Error::call_filter("upper", tera::Error::msg("not a string")),
+        );
+        assert_eq!(expression(&chain(&error)).as_deref(), Some("upper"));
+
+        let error = tera::Error::msg("Failed to render 'a'");
+        assert_eq!(expression(&chain(&error)), None);
+    }
+
+    #[test]
+    fn contains_whole_names_only() {
+        assert!(contains("{{ room.title }}", "room.title"));
+        assert!(contains("{{room.title|upper}}", "room.title"));
+        assert!(!contains("{{ room.titles }}", "room.title"));
+        assert!(!contains("{{ other_room.title }}", "room.title"));
+        assert!(!contains("{{ a.room.title }}", "room.title"));
+    }
+
+    #[test]
+    fn finds_macro_bodies() {
+        let source = "a\n{% macro one(x) %}\nb\n{% endmacro one %}\n{% macro two() %}\nc\n";
+        assert_eq!(macro_body(source, None), Some((0, 6)));
+        assert_eq!(macro_body(source, Some("one")), Some((1, 4)));
+        // not closed: until the end
+        assert_eq!(macro_body(source, Some("two")), Some((4, 6)));
+        assert_eq!(macro_body(source, Some("three")), None);
+    }
+
+    #[test]
+    fn describes_items() {
+        let room = json!({"roomID": 1, "roomName": "Barn"});
+        assert_eq!(describe("room", &room), "room Barn (1)");
+        let action = json!({"actionID": 4, "type": "Open"});
+        assert_eq!(describe("action", &action), "action Open (4)");
+        assert_eq!(
+            describe("level", &json!({"levelName": "farm"})),
+            "level farm"
+        );
+        let batch = json!({"name": "barn", "roomIDs": [1]});
+        assert_eq!(describe("batch", &batch), "batch barn");
+        assert_eq!(describe("count", &json!(3)), "count = 3");
+        let long = describe("text", &json!("x".repeat(100)));
+        assert_eq!(long.chars().count(), "text = ".len() + 60);
+        assert!(long.ends_with("..."));
+    }
+
+    #[test]
+    fn reads_loop_names_and_macro_params() {
+        assert_eq!(loop_names("for room in rooms"), ["room"]);
+        assert_eq!(loop_names("for key, value in map"), ["key", "value"]);
+        assert!(loop_names("if rooms").is_empty());
+        assert_eq!(
+            macro_params("macro name(room, upper=true)"),
+            ["room", "upper"]
+        );
+        assert!(macro_params("macro name()").is_empty());
+    }
+
+    #[test]
+    fn traces_loops_and_macros() {
+        let source = "{% for room in rooms %}{{ room.roomName }}{% endfor %}";
+        assert_eq!(
+            trace_source(source),
+            "{% for room in rooms %}{{ __pray_enter(name=\"room\", value=room) }}\
+             {{ room.roomName }}{{ __pray_leave(name=\"room\") }}{% endfor %}"
+        );
+        let source = "{% macro name(room) %}x{% endmacro %}";
+        assert_eq!(
+            trace_source(source),
+            "{% macro name(room) %}{{ __pray_enter(name=\"room\", value=room) }}\
+             x{{ __pray_leave(name=\"room\") }}{% endmacro %}"
+        );
+        let source = "{% raw %}{% for a in b %}{% endraw %}";
+        assert_eq!(trace_source(source), source);
+    }
+}
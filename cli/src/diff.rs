@@ -0,0 +1,140 @@
+use pray_engine::diff::{Diff, FieldChange, ObjectDiff, Status};
+use serde_json::Value;
+use similar::{ChangeTag, TextDiff};
+use std::io::{self, IsTerminal, StdoutLock, Write};
+
+fn sign(status: Status) -> (&'static str, &'static str) {
+    match status {
+        Status::Added => ("+", "\x1b[32m"),
+        Status::Removed => ("-", "\x1b[31m"),
+        Status::Changed => ("~", "\x1b[33m"),
+    }
+}
+
+/// `value` on one line, strings without quotes
+fn short(value: &Value) -> String {
+    match value {
+        Value::String(str) => str.clone(),
+        value => value.to_string(),
+    }
+}
+
+struct Printer<'a> {
+    out: StdoutLock<'a>,
+    colors: bool,
+}
+
+impl Printer<'_> {
+    fn line(&mut self, indent: usize, color: &str, line: &str) -> io::Result<()> {
+        if self.colors {
+            writeln!(
+                self.out,
+                "{:indent$}{}{}\x1b[0m",
+                "",
+                color,
+                line,
+                indent = indent
+            )
+        } else {
+            writeln!(self.out, "{:indent$}{}", "", line, indent = indent)
+        }
+    }
+
+    fn heading(&mut self, indent: usize, status: Status, heading: String) -> io::Result<()> {
+        let (sign, color) = sign(status);
+        self.line(indent, color, &format!("{} {}", sign, heading))
+    }
+
+    fn change(&mut self, indent: usize, change: &FieldChange) -> io::Result<()> {
+        let (old, new) = (short(&change.old), short(&change.new));
+        if !old.contains('\n') && !new.contains('\n') {
+            return self.line(indent, "", &format!("{}: {} -> {}", change.field, old, new));
+        }
+
+        // descriptions span several lines, show which ones changed
+        self.line(indent, "", &format!("{}:", change.field))?;
+        for line in TextDiff::from_lines(old.as_str(), new.as_str()).iter_all_changes() {
+            let (sign, color) = match line.tag() {
+                ChangeTag::Delete => ("-", "\x1b[31m"),
+                ChangeTag::Insert => ("+", "\x1b[32m"),
+                ChangeTag::Equal => (" ", ""),
+            };
+            let text = line.to_string_lossy();
+            self.line(indent + 2, color, &format!("{} {}", sign, text.trim_end()))?;
+        }
+        Ok(())
+    }
+
+    fn object(&mut self, indent: usize, object: &ObjectDiff) -> io::Result<()> {
+        let heading = format!("object {} {}", object.obj_id, object.obj_description);
+        self.heading(indent, object.status, heading)?;
+        for change in &object.changes {
+            self.change(indent + 4, change)?;
+        }
+
+        for action in &object.actions {
+            let heading = format!("action {} {}", action.action_id, action.ttype);
+            self.heading(indent + 2, action.status, heading)?;
+            for change in &action.changes {
+                self.change(indent + 6, change)?;
+            }
+        }
+
+        // the contents of a container
+        for object in &object.contains {
+            self.object(indent + 2, object)?;
+        }
+        Ok(())
+    }
+}
+
+/// Print the diff as a tree of rooms, objects and actions, then the players and locales
+pub fn print(diff: &Diff) -> io::Result<()> {
+    let stdout = io::stdout();
+    let mut printer = Printer {
+        colors: stdout.is_terminal(),
+        out: stdout.lock(),
+    };
+    if diff.is_empty() {
+        return writeln!(printer.out, "no changes");
+    }
+
+    for room in &diff.rooms {
+        let name = match room
+            .changes
+            .iter()
+            .find(|change| change.field == "roomName")
+        {
+            Some(change) => format!("{} -> {}", short(&change.old), short(&change.new)),
+            None => room.room_name.clone(),
+        };
+        printer.heading(0, room.status, format!("room {} ({})", name, room.room_id))?;
+        for change in &room.changes {
+            printer.change(4, change)?;
+        }
+
+        for object in &room.objects {
+            printer.object(2, object)?;
+        }
+    }
+    for player in &diff.players {
+        printer.heading(
+            0,
+            player.status,
+            format!("player of level {}", player.level_name),
+        )?;
+        for change in &player.changes {
+            printer.change(4, change)?;
+        }
+        for object in &player.inventory {
+            printer.object(2, object)?;
+        }
+    }
+    for locale in &diff.locales {
+        printer.heading(0, locale.status, format!("locale {}", locale.locale))?;
+        for change in &locale.changes {
+            printer.change(4, change)?;
+        }
+    }
+    Ok(())
+}
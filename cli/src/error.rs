@@ -0,0 +1,91 @@
+use std::error::Error as _;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Everything that can go wrong while generating files
+#[derive(Debug)]
+pub enum Error {
+    Io(PathBuf, io::Error),
+    Config(PathBuf, String),
+    Template(tera::Error),
+    /// A template that failed to render, with where and on what
+    Render(Box<crate::diagnose::Failure>),
+    /// Two templates (or two rooms of one template) render to the same file
+    Collision(PathBuf, String, String),
+    /// A file name rendered to a path outside the target folder, with what rendered it
+    OutsideTarget(String, String),
+    Watch(notify::Error),
+    Manifest(PathBuf, String),
+    /// The world goes over the budget of the manifest, one line per limit
+    Budget(Vec<String>),
+    Usage(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(path, e) => write!(f, "{}: {}", path.to_string_lossy(), e),
+            Error::Config(path, e) => write!(f, "invalid config {}: {}", path.to_string_lossy(), e),
+            Error::Template(e) => {
+                write!(f, "{}", e)?;
+                // tera nests the interesting part of the message in the source chain
+                let mut source = e.source();
+                while let Some(cause) = source {
+                    write!(f, "\n  caused by: {}", cause)?;
+                    source = cause.source();
+                }
+                Ok(())
+            }
+            Error::Render(failure) => write!(f, "{}", failure),
+            Error::Collision(path, first, second) => write!(
+                f,
+                "{} is generated by both {} and {}",
+                path.to_string_lossy(),
+                first,
+                second
+            ),
+            Error::OutsideTarget(name, source) => write!(
+                f,
+                "{} renders to {}, which is not inside the target folder",
+                source, name
+            ),
+            Error::Watch(e) => write!(f, "watch error: {}", e),
+            Error::Manifest(path, e) => write!(f, "{}: {}", path.to_string_lossy(), e),
+            Error::Budget(exceeded) => {
+                write!(f, "over budget:")?;
+                for line in exceeded {
+                    write!(f, "\n  {}", line)?;
+                }
+                Ok(())
+            }
+            Error::Usage(usage) => write!(f, "{}", usage),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<tera::Error> for Error {
+    fn from(e: tera::Error) -> Self {
+        Error::Template(e)
+    }
+}
+
+impl From<notify::Error> for Error {
+    fn from(e: notify::Error) -> Self {
+        Error::Watch(e)
+    }
+}
+
+impl Error {
+    /// A config that could not be read, loaded or saved
+    pub fn config(path: &Path, e: pray_engine::format::Error) -> Self {
+        match e {
+            pray_engine::format::Error::Io(e) => Error::Io(path.to_path_buf(), e),
+            e => Error::Config(path.to_path_buf(), e.to_string()),
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use tera::Value;
+
+/// Convert line breaks
+pub fn linebreaks(value: &Value, params: &HashMap<String, Value>) -> tera::Result<Value> {
+    let text: String = if let Value::String(s) = value {
+        s.to_string()
+    } else {
+        return Err("The linebreaks filter can only be applied to strings".into());
+    };
+    let mut replacement_text: String = String::from_str("\\n").expect("failed to convert");
+    let default_value = Value::String(replacement_text);
+    let replacement = params.get("to");
+    let replacement_value = if let Option::Some(v) = replacement {
+        v
+    } else {
+        &default_value
+    };
+
+    if let Value::String(s) = replacement_value {
+        replacement_text = s.to_owned()
+    } else {
+        return Err("The linebreaks filter can only be applied to strings".into());
+    };
+    Ok(Value::String(
+        text.replace("\r\n", replacement_text.as_str())
+            .replace("\n", replacement_text.as_str()),
+    ))
+}
+
+/// Turn a string into something usable as a file name, e.g. `Eli's Barn` => `eli-s-barn`
+pub fn slug(value: &Value, _params: &HashMap<String, Value>) -> tera::Result<Value> {
+    let text = if let Value::String(s) = value {
+        s
+    } else {
+        return Err("The slug filter can only be applied to strings".into());
+    };
+
+    let mut slug = String::with_capacity(text.len());
+    for c in text.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    Ok(Value::String(slug.trim_end_matches('-').to_string()))
+}
+
+/// Turn a string into a Rust string literal, quotes and escapes included
+pub fn rust_string(value: &Value, _params: &HashMap<String, Value>) -> tera::Result<Value> {
+    let text = if let Value::String(s) = value {
+        s
+    } else {
+        return Err("The rust_string filter can only be applied to strings".into());
+    };
+    Ok(Value::String(format!("{:?}", text)))
+}
+
+/// Turn the integers of a value into strings, JavaScript numbers lose the IDs above 2^53.
+/// The integers of the fields named in `except`, e.g. counts, are left alone.
+pub fn string_ids(value: &Value, params: &HashMap<String, Value>) -> tera::Result<Value> {
+    let except: Vec<&str> = match params.get("except") {
+        None => Vec::new(),
+        Some(Value::Array(keys)) => keys.iter().filter_map(Value::as_str).collect(),
+        Some(_) => return Err("The except argument of string_ids must be a list of keys".into()),
+    };
+    Ok(ids_to_strings(value, &except))
+}
+
+/// See `string_ids`, for the JSON printed by the commands
+pub fn ids_to_strings(value: &Value, except: &[&str]) -> Value {
+    match value {
+        Value::Number(number) if number.is_u64() || number.is_i64() => {
+            Value::String(number.to_string())
+        }
+        Value::Array(values) => Value::Array(
+            values
+                .iter()
+                .map(|value| ids_to_strings(value, except))
+                .collect(),
+        ),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| {
+                    let value = if except.contains(&key.as_str()) {
+                        value.clone()
+                    } else {
+                        ids_to_strings(value, except)
+                    };
+                    (key.clone(), value)
+                })
+                .collect(),
+        ),
+        value => value.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn string_ids_keep_big_ids_exact() {
+        let batch = json!({ "roomIDs": [11107137240536497418u64], "writes": 3, "name": "batch_0" });
+        let params = HashMap::from([("except".to_string(), json!(["writes"]))]);
+        assert_eq!(
+            string_ids(&batch, &params).unwrap(),
+            json!({ "roomIDs": ["11107137240536497418"], "writes": 3, "name": "batch_0" })
+        );
+    }
+
+    #[test]
+    fn slug_keeps_lowercase_words() {
+        let slugged = |text: &str| slug(&json!(text), &HashMap::new()).unwrap();
+        assert_eq!(slugged("Eli's Barn"), json!("eli-s-barn"));
+        assert_eq!(slugged("  The  Loft! "), json!("the-loft"));
+        assert_eq!(slugged("Écurie 2"), json!("écurie-2"));
+        // nothing left to name a file with
+        assert_eq!(slugged("../"), json!(""));
+        assert!(slug(&json!(3), &HashMap::new()).is_err());
+    }
+}
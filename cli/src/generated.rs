@@ -0,0 +1,76 @@
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Record of the files pray generated, kept in the folder it generated them into
+pub const GENERATED_FILE: &str = ".pray-generated.json";
+
+/// A generated file as it was last written
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Entry {
+    pub hash: String,
+    pub template: String,
+    pub config: String,
+}
+
+/// Generated files by target, then by path relative to the folder
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct Generated {
+    pub targets: BTreeMap<String, BTreeMap<String, Entry>>,
+}
+
+/// FNV-1a of the content, unlike `DefaultHasher` it does not change between Rust releases
+pub fn hash(content: &str) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in content.bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{:016x}", hash)
+}
+
+impl Generated {
+    pub fn path(root: &Path) -> PathBuf {
+        root.join(GENERATED_FILE)
+    }
+
+    /// The record of `root`, empty when nothing was generated there yet
+    pub fn load(root: &Path) -> Result<Generated> {
+        let path = Generated::path(root);
+        match fs::read_to_string(&path) {
+            Ok(str) => serde_json::from_str(&str).map_err(|e| Error::Config(path, e.to_string())),
+            Err(_) => Ok(Generated::default()),
+        }
+    }
+
+    pub fn serialize(&self) -> String {
+        serde_json::to_string_pretty(self).expect("failed to serialize the generated files") + "\n"
+    }
+
+    /// The entry of a generated file, looked up from its full path
+    pub fn entry(&self, root: &Path, target: &str, path: &Path) -> Option<&Entry> {
+        self.targets.get(target)?.get(&relative(root, path))
+    }
+}
+
+/// Key of `path` in the record of `root`
+pub fn relative(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hashes_are_stable() {
+        assert_eq!(hash(""), "cbf29ce484222325");
+        assert_eq!(hash("a"), "af63dc4c8601ec8c");
+    }
+}
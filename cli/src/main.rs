@@ -1,139 +1,714 @@
-use glob::glob;
-use notify::{RecursiveMode, Result, Watcher};
-use pray_engine::parse;
-use pray_engine::Config;
+mod args;
+mod builtin;
+mod diagnose;
+mod diff;
+mod error;
+mod filters;
+mod generated;
+mod manifest;
+mod migrate;
+mod output;
+mod render;
+mod snapshot;
+mod watch;
+
+use args::Args;
+use builtin::Set;
+use error::{Error, Result};
+use manifest::Manifest;
+use output::Output;
+use pray_engine::format::Format;
+use pray_engine::locale::Coverage;
+use pray_engine::partition::Partition;
+use pray_engine::stats::{self, Budget, LevelStats};
+use pray_engine::validate::Severity;
+use pray_engine::{merge, Config};
+use render::{load_config, load_templates_or_builtin, render};
+use serde::Serialize;
 use std::collections::HashMap;
 use std::fs;
-use std::io;
-use std::path::Path;
-use std::str::FromStr;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
 use tera::Context;
-use tera::Tera;
-use tera::Value;
 
-fn get_file_list(parent_dir: &str, pattern: &str) -> Result<Vec<String>> {
-    let mut file_list = Vec::new();
+const USAGE: &str = "usage:
+  pray build [--target <name>] [--manifest <pray.toml>] [--watch] [--dry-run] [--force]
+  pray check [--target <name>] [--manifest <pray.toml>]
+  pray diff <old-config> <new-config> [--json]
+  pray init [<folder>] [--set <name>] [--force]
+  pray locales [<config>] [--manifest <pray.toml>] [--json] [--strict]
+  pray migrate [<config>...] [--manifest <pray.toml>] [--dry-run]
+  pray schema <folder>
+  pray stats [<config>] [--manifest <pray.toml>] [--json] [--max-text-bytes <n>] [--max-writes <n>] [--max-felts <n>] [--max-rooms <n>]
+  pray test <templates-folder> <fixture>... [--templates <folder>]... [--defaults <set>] [--update]
+  pray test [--target <name>] [--manifest <pray.toml>] [--update]
+  pray validate [<config>] [--manifest <pray.toml>] [--json]
+  pray <config.yml> <templates-folder> <target-folder> [--templates <folder>]... [--defaults <set>] [--watch] [--dry-run] [--check] [--force]";
 
-    for entry in glob(&pattern).expect("invalid pattern") {
-        match entry {
-            Ok(path) => {
-                if let Ok(relative_path) = path.strip_prefix(parent_dir) {
-                    file_list.push(relative_path.to_string_lossy().into_owned());
-                }
+/// The flags and options of each command, as in `USAGE`
+fn known_flags(command: Option<&str>) -> &'static [&'static str] {
+    match command {
+        Some("build") => &["--target", "--manifest", "--watch", "--dry-run", "--force"],
+        Some("check") => &["--target", "--manifest", "--templates", "--defaults"],
+        Some("diff") => &["--json"],
+        Some("init") => &["--set", "--force"],
+        Some("locales") => &["--manifest", "--json", "--strict"],
+        Some("migrate") => &["--manifest", "--dry-run"],
+        Some("schema") => &[],
+        Some("stats") => &[
+            "--manifest",
+            "--json",
+            "--max-text-bytes",
+            "--max-writes",
+            "--max-felts",
+            "--max-rooms",
+        ],
+        Some("test") => &[
+            "--target",
+            "--manifest",
+            "--templates",
+            "--defaults",
+            "--update",
+        ],
+        Some("validate") => &["--manifest", "--json"],
+        _ => &[
+            "--target",
+            "--manifest",
+            "--templates",
+            "--defaults",
+            "--watch",
+            "--dry-run",
+            "--check",
+            "--force",
+        ],
+    }
+}
+
+/// Where `pray init` writes the templates when no folder is given
+const INIT_FOLDER: &str = "templates";
+
+/// Renders every output in memory, built once or on each change when watching
+type Build = Box<dyn Fn() -> Result<Vec<Output>>>;
+
+/// Load the config and the templates and render every output in memory
+fn build_paths(
+    config_path: &Path,
+    template_paths: &[PathBuf],
+    defaults: Option<&Set>,
+    destination_path: &Path,
+) -> Result<Vec<Output>> {
+    let config = load_config(config_path)?;
+    let (tera, files) = load_templates_or_builtin(template_paths, defaults)?;
+    render(
+        &config,
+        config_path,
+        None,
+        &Context::new(),
+        destination_path,
+        &tera,
+        &files,
+    )
+}
+
+/// The config of a manifest, merged from the markdown folders, or the config file, and the included configs
+fn manifest_config(manifest: &Manifest) -> Result<Config> {
+    let config_path = manifest.path(&manifest.config);
+    let mut parts = Vec::new();
+    if manifest.markdown.is_empty() {
+        parts = merge::load_parts(&config_path).map_err(|e| Error::config(&config_path, e))?;
+    }
+    for markdown in &manifest.markdown {
+        let folder = manifest.path(markdown).to_string_lossy().into_owned();
+        let config = letusprayfromfiles::build_config(&folder)
+            .map_err(|e| Error::Config(PathBuf::from(&folder), e.to_string()))?;
+        parts.push((folder, config));
+    }
+    for include in &manifest.include {
+        let path = manifest.path(include);
+        parts.extend(merge::load_parts(&path).map_err(|e| Error::config(&path, e))?);
+    }
+    merge::merge(parts).map_err(|e| Error::config(&config_path, e))
+}
+
+/// Render the targets of a manifest, plus the config itself when it is generated from markdown
+fn build_manifest(manifest: &Manifest, target: Option<&str>) -> Result<Vec<Output>> {
+    let mut outputs = Vec::new();
+
+    let config_path = manifest.path(&manifest.config);
+    let config = manifest_config(manifest)?;
+    let exceeded = manifest
+        .budget
+        .exceeded(&stats::stats(&config, manifest.batches.as_ref()));
+    if !exceeded.is_empty() {
+        return Err(Error::Budget(exceeded));
+    }
+    if !manifest.markdown.is_empty() {
+        let format = Format::from_path(&config_path).map_err(|e| Error::config(&config_path, e))?;
+        let folders: Vec<_> = manifest
+            .markdown
+            .iter()
+            .map(|markdown| manifest.path(markdown).to_string_lossy().into_owned())
+            .collect();
+        outputs.push(Output {
+            path: config_path.clone(),
+            content: format.serialize(&config),
+            root: manifest.root.clone(),
+            target: "markdown".to_string(),
+            template: folders.join(", "),
+            config: config_path.to_string_lossy().into_owned(),
+        });
+    }
+
+    let mut sources: HashMap<PathBuf, String> = HashMap::new();
+    for (name, target) in manifest.targets(target)? {
+        let (tera, files) = manifest.templates(name, target)?;
+        let context = manifest.context(target)?;
+        let destination_path = manifest.path(&target.output);
+        let outputs_of_target = render(
+            &config,
+            &config_path,
+            manifest.batches.as_ref(),
+            &context,
+            &destination_path,
+            &tera,
+            &files,
+        )?;
+        for mut output in outputs_of_target {
+            output.target = name.clone();
+            let source = format!("target {}", name);
+            if let Some(previous) = sources.insert(output.path.clone(), source.clone()) {
+                return Err(Error::Collision(output.path, previous, source));
             }
-            Err(e) => eprintln!("Error: {:?}", e),
+            outputs.push(output);
         }
     }
+    Ok(outputs)
+}
+
+/// Write the JSON Schemas of the config and of the markdown YAML blocks into `folder`
+fn write_schemas(folder: &Path) -> Result<()> {
+    fs::create_dir_all(folder).map_err(|e| Error::Io(folder.to_path_buf(), e))?;
+    for (name, schema) in pray_engine::schema::schemas() {
+        let path = folder.join(name);
+        let content = serde_json::to_string_pretty(&schema).expect("failed to serialize schema");
+        fs::write(&path, content + "\n").map_err(|e| Error::Io(path.clone(), e))?;
+        println!("wrote {}", path.to_string_lossy());
+    }
+    Ok(())
+}
 
-    Ok(file_list)
+/// The built-in set given with `option`
+fn set_of(args: &Args, option: &str) -> Result<Option<&'static Set>> {
+    match args.option(option) {
+        None => Ok(None),
+        Some(name) => match builtin::find(name) {
+            Some(set) => Ok(Some(set)),
+            None => Err(Error::Usage(format!(
+                "unknown template set `{}`, the built-in sets are:\n{}",
+                name,
+                builtin::list()
+            ))),
+        },
+    }
 }
 
-/// Convert line breaks
-pub fn linebreaks(value: &Value, params: &HashMap<String, Value>) -> tera::Result<Value> {
-    let text: String = if let Value::String(s) = value {
-        s.to_string()
-    } else {
-        return Err("The linebreaks filter can only be applied to strings".into());
-    };
-    let mut replacement_text: String = String::from_str("\\n").expect("failed to convert");
-    let default_value = Value::String(replacement_text);
-    let replacement = params.get("to");
-    let replacement_value = if let Option::Some(v) = replacement {
-        v
-    } else {
-        &default_value
-    };
+/// The built-in set a templates folder given on the command line overrides
+fn defaults_of(args: &Args) -> Result<Option<&'static Set>> {
+    set_of(args, "--defaults")
+}
 
-    if let Value::String(s) = replacement_value {
-        replacement_text = s.to_owned()
-    } else {
-        return Err("The linebreaks filter can only be applied to strings".into());
+/// The templates folder given on the command line, then those of `--templates`
+fn template_paths_of(template_path: &str, args: &Args) -> Vec<PathBuf> {
+    let mut template_paths = vec![PathBuf::from(template_path)];
+    template_paths.extend(args.values("--templates").into_iter().map(PathBuf::from));
+    template_paths
+}
+
+/// Copy the templates of a built-in set into `folder` for a project to customize them,
+/// files already there are kept unless `force` is set
+fn init(set: &Set, folder: &Path, force: bool) -> Result<()> {
+    for (name, content) in set.templates {
+        let path = folder.join(name);
+        if path.exists() && !force {
+            println!(
+                "kept {}, it already exists, use --force to overwrite it",
+                path.to_string_lossy()
+            );
+            continue;
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| Error::Io(parent.to_path_buf(), e))?;
+        }
+        fs::write(&path, content).map_err(|e| Error::Io(path.clone(), e))?;
+        println!("wrote {}", path.to_string_lossy());
+    }
+    Ok(())
+}
+
+/// The manifest given with `--manifest`, or found from the current folder
+fn manifest_path(args: &Args) -> Result<PathBuf> {
+    match args.option("--manifest") {
+        Some(path) => Ok(PathBuf::from(path)),
+        None => Manifest::find(),
+    }
+}
+
+fn manifest(args: &Args) -> Result<Manifest> {
+    Manifest::load(&manifest_path(args)?)
+}
+
+/// The config given on the command line, or the config of the manifest
+fn config_of(args: &Args) -> Result<Config> {
+    match args.positional() {
+        [] => manifest_config(&manifest(args)?),
+        [config_path] => load_config(Path::new(config_path)),
+        _ => Err(Error::Usage(USAGE.to_string())),
+    }
+}
+
+/// The budget of the manifest, with the limits given on the command line over it
+fn budget_of(args: &Args) -> Result<Budget> {
+    let mut budget = match args.positional() {
+        [] => manifest(args)?.budget,
+        _ => Budget::default(),
     };
-    Ok(Value::String(
-        text.replace("\r\n", replacement_text.as_str())
-            .replace("\n", replacement_text.as_str()),
-    ))
+    let limits = [
+        ("--max-text-bytes", &mut budget.max_text_bytes),
+        ("--max-writes", &mut budget.max_writes),
+        ("--max-felts", &mut budget.max_felts),
+        ("--max-rooms", &mut budget.max_rooms),
+    ];
+    for (option, limit) in limits {
+        if let Some(value) = args.option(option) {
+            let value = value
+                .parse()
+                .map_err(|_| Error::Usage(format!("{} expects a number\n{}", option, USAGE)))?;
+            *limit = Some(value);
+        }
+    }
+    Ok(budget)
 }
 
-fn write(config_path: &String, destination_path: &String, tera: &Tera, files: &Vec<String>) {
-    fs::create_dir_all(destination_path).expect("failed to create the destination folder");
+/// The batches of the manifest, none for a config given on the command line
+fn batches_of(args: &Args) -> Result<Option<Partition>> {
+    match args.positional() {
+        [] => Ok(manifest(args)?.batches),
+        _ => Ok(None),
+    }
+}
 
-    let config_string = fs::read_to_string(config_path).expect("Unable to read config file");
-    let config: Config = parse(&config_string);
+/// Render every fixture given on the command line, or of the manifest for each of its targets,
+/// and compare the outputs with their snapshots
+fn test_snapshots(args: &Args) -> Result<Vec<snapshot::Mismatch>> {
+    let mut mismatches = Vec::new();
+    match args.positional() {
+        [] => {
+            let manifest = manifest(args)?;
+            let paths: Vec<PathBuf> = manifest
+                .fixtures
+                .iter()
+                .map(|path| manifest.path(path))
+                .collect();
+            for fixture in snapshot::fixtures(&paths)? {
+                for (name, target) in manifest.targets(args.option("--target"))? {
+                    let (tera, files) = manifest.templates(name, target)?;
+                    // each target has its own snapshots
+                    let folder = snapshot::folder(&fixture).join(name);
+                    let outputs = snapshot::render_fixture(
+                        &fixture,
+                        &tera,
+                        &files,
+                        manifest.batches.as_ref(),
+                        &manifest.context(target)?,
+                        &folder,
+                    )?;
+                    mismatches.extend(snapshot::compare(&outputs, &folder));
+                }
+            }
+        }
+        [template_path, fixtures @ ..] if !fixtures.is_empty() => {
+            let template_paths = template_paths_of(template_path, args);
+            let (tera, files) = load_templates_or_builtin(&template_paths, defaults_of(args)?)?;
+            let paths: Vec<PathBuf> = fixtures.iter().map(PathBuf::from).collect();
+            for fixture in snapshot::fixtures(&paths)? {
+                let folder = snapshot::folder(&fixture);
+                let outputs = snapshot::render_fixture(
+                    &fixture,
+                    &tera,
+                    &files,
+                    None,
+                    &Context::new(),
+                    &folder,
+                )?;
+                mismatches.extend(snapshot::compare(&outputs, &folder));
+            }
+        }
+        _ => return Err(Error::Usage(USAGE.to_string())),
+    }
+    Ok(mismatches)
+}
 
-    for file in files {
-        let destination_file_name = file
-            .strip_suffix(".tera")
-            .expect("failted to remove .tera extension to file name");
-        let mut destination_file_path = destination_path.to_owned();
-        destination_file_path.push_str("/");
-        destination_file_path.push_str(destination_file_name); // TODO
+/// The fields of `pray stats --json` that are counts rather than IDs
+const STATS_COUNTS: &[&str] = &[
+    "rooms",
+    "objects",
+    "actions",
+    "npcs",
+    "textBytes",
+    "bytes",
+    "writes",
+    "felts",
+];
 
-        let context = &Context::from_serialize(&config)
-            .expect("Faild to serialise the config into tera context");
-        let str = tera
-            .render(file, &context) // TODO
-            .expect("unable to render template");
-        fs::write(destination_file_path, str).expect("Unable to write file");
+/// `value` as pretty JSON with its IDs as strings, see `filters::string_ids`
+fn to_json(value: &impl Serialize, except: &[&str]) -> String {
+    let value = serde_json::to_value(value).expect("failed to serialize");
+    serde_json::to_string_pretty(&filters::ids_to_strings(&value, except))
+        .expect("failed to serialize")
+}
+
+fn print_stats(stats: &[LevelStats]) {
+    for level in stats {
+        println!(
+            "level {}: {} rooms, {} objects, {} actions, {} npcs, {} bytes of text",
+            level.level_name,
+            level.rooms,
+            level.objects,
+            level.actions,
+            level.npcs,
+            level.text_bytes
+        );
+        println!("  longest texts:");
+        for text in &level.longest {
+            println!("    {:>6} bytes  {}", text.bytes, text.id);
+        }
+        println!("  functions:");
+        for function in &level.functions {
+            println!(
+                "    {:>6} writes {:>6} felts  {}",
+                function.writes, function.felts, function.name
+            );
+        }
+    }
+}
+
+/// Print how much of the world each locale translates, with the texts left to translate
+fn print_coverage(config: &Config, coverage: &[Coverage]) {
+    let texts = pray_engine::locale::texts(config);
+    for locale in coverage {
+        println!(
+            "{}: {}/{} texts translated",
+            locale.locale,
+            locale.translated(),
+            locale.total
+        );
+        for id in &locale.missing {
+            let text = texts[id].lines().next().unwrap_or_default();
+            println!("  missing {} `{}`", id, text);
+        }
+        for id in &locale.unknown {
+            println!("  unknown {}", id);
+        }
+    }
+    if coverage.is_empty() {
+        println!("no translations");
     }
 }
 
-fn main() -> Result<()> {
-    let config_path = std::env::args().nth(1).expect("path to config file");
-    println!("path: {:?}", config_path);
+fn run() -> Result<()> {
+    let mut args = Args::parse(std::env::args().skip(1));
+    let command = args.command(&[
+        "build", "check", "diff", "init", "locales", "migrate", "schema", "stats", "test",
+        "validate",
+    ]);
+    if let Some(unknown) = args.unknown(known_flags(command.as_deref())) {
+        return Err(Error::Usage(format!(
+            "unknown option {}\n{}",
+            unknown, USAGE
+        )));
+    }
 
-    let template_path = std::env::args().nth(2).expect("path to template folder");
-    println!("template_path: {:?}", template_path);
+    if command.as_deref() == Some("diff") {
+        let [old_path, new_path] = args.positional() else {
+            return Err(Error::Usage(USAGE.to_string()));
+        };
+        let old = load_config(Path::new(old_path))?;
+        let new = load_config(Path::new(new_path))?;
+        let diff = pray_engine::diff::diff(&old, &new);
+        let printed = if args.flag("--json") {
+            writeln!(io::stdout().lock(), "{}", to_json(&diff, &[]))
+        } else {
+            diff::print(&diff)
+        };
+        return match printed {
+            // e.g. piped into `head`, which stops reading once it has enough
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+            printed => printed.map_err(|e| Error::Io(PathBuf::from("stdout"), e)),
+        };
+    }
 
-    let destination_path = std::env::args().nth(3).expect("path to destination folder");
-    println!("destination_path: {:?}", destination_path);
+    if command.as_deref() == Some("init") {
+        let folder = match args.positional() {
+            [] => Path::new(INIT_FOLDER),
+            [folder] => Path::new(folder),
+            _ => return Err(Error::Usage(USAGE.to_string())),
+        };
+        let set = match set_of(&args, "--set")? {
+            Some(set) => set,
+            None => builtin::find(builtin::DEFAULT).expect("unknown default template set"),
+        };
+        return init(set, folder, args.flag("--force"));
+    }
 
-    let watch = if let Some(arg) = std::env::args().nth(4) {
-        arg == "--watch"
-    } else {
-        false
-    };
+    if command.as_deref() == Some("schema") {
+        return match args.positional() {
+            [folder] => write_schemas(Path::new(folder)),
+            _ => Err(Error::Usage(USAGE.to_string())),
+        };
+    }
+
+    if command.as_deref() == Some("locales") {
+        let config = config_of(&args)?;
+        let coverage = pray_engine::locale::coverage(&config);
+        if args.flag("--json") {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&coverage).expect("failed to serialize coverage")
+            );
+        } else {
+            print_coverage(&config, &coverage);
+        }
+        let complete = coverage
+            .iter()
+            .all(|locale| locale.missing.is_empty() && locale.unknown.is_empty());
+        if args.flag("--strict") && !complete {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    if command.as_deref() == Some("stats") {
+        let config = config_of(&args)?;
+        let budget = budget_of(&args)?;
+        let stats = stats::stats(&config, batches_of(&args)?.as_ref());
+        let exceeded = budget.exceeded(&stats);
+        if args.flag("--json") {
+            let report = serde_json::json!({ "levels": stats, "exceeded": exceeded });
+            println!("{}", to_json(&report, STATS_COUNTS));
+        } else {
+            print_stats(&stats);
+        }
+        if !exceeded.is_empty() {
+            return Err(Error::Budget(exceeded));
+        }
+        return Ok(());
+    }
+
+    if command.as_deref() == Some("test") {
+        let mismatches = test_snapshots(&args)?;
+        snapshot::print(&mismatches);
+        if args.flag("--update") {
+            snapshot::update(&mismatches)?;
+            println!("{} snapshots updated", mismatches.len());
+        } else if !mismatches.is_empty() {
+            println!(
+                "{} snapshots do not match, run with --update to accept the changes",
+                mismatches.len()
+            );
+            std::process::exit(1);
+        } else {
+            println!("every snapshot matches");
+        }
+        return Ok(());
+    }
+
+    if command.as_deref() == Some("validate") {
+        let config = config_of(&args)?;
+        let problems = pray_engine::validate::validate(&config);
+        if args.flag("--json") {
+            println!("{}", to_json(&problems, &[]));
+        } else {
+            for problem in &problems {
+                println!("{}", problem);
+            }
+            if problems.is_empty() {
+                println!("no problems");
+            }
+        }
+        // warnings are worth reading but do not break the world
+        if problems.iter().any(|p| p.severity == Severity::Error) {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    if command.as_deref() == Some("migrate") {
+        let paths: Vec<PathBuf> = match args.positional() {
+            [] => {
+                let manifest = manifest(&args)?;
+                vec![manifest.path(&manifest.config)]
+            }
+            paths => paths.iter().map(PathBuf::from).collect(),
+        };
+        return migrate::migrate(&paths, args.flag("--dry-run"));
+    }
 
-    let mut template_glob = template_path.clone();
-    template_glob.push_str("/**/*.tera");
+    // `pray check ...` is an alias for `pray ... --check`
+    let check_only = args.flag("--check") || command.as_deref() == Some("check");
+    let watch = args.flag("--watch");
+    let dry_run = args.flag("--dry-run");
+    let force = args.flag("--force");
 
-    let mut tera = match Tera::new(template_glob.as_str()) {
-        Ok(t) => t,
-        Err(e) => {
-            println!("Parsing error(s): {}", e);
-            ::std::process::exit(1);
+    let (build, inputs): (Build, Vec<PathBuf>) = match args.positional() {
+        [] => {
+            let manifest_path = manifest_path(&args)?;
+            let target = args.option("--target").map(str::to_string);
+
+            let manifest = Manifest::load(&manifest_path)?;
+            manifest.targets(target.as_deref())?;
+            let mut inputs = manifest.inputs();
+            inputs.push(manifest_path.clone());
+
+            // the manifest is loaded again on each build so watch mode picks up its changes
+            let build = move || {
+                let manifest = Manifest::load(&manifest_path)?;
+                build_manifest(&manifest, target.as_deref())
+            };
+            (Box::new(build), inputs)
+        }
+        [config_path, template_path, destination_path] if command.as_deref() != Some("build") => {
+            println!("path: {:?}", config_path);
+            println!("template_path: {:?}", template_path);
+            println!("destination_path: {:?}", destination_path);
+
+            let config_path = PathBuf::from(config_path);
+            let template_paths = template_paths_of(template_path, &args);
+            let destination_path = PathBuf::from(destination_path);
+            let mut inputs = merge::files(&config_path);
+            // built-in template sets do not change while watching
+            inputs.extend(template_paths.iter().filter(|path| path.is_dir()).cloned());
+            let defaults = defaults_of(&args)?;
+            let build =
+                move || build_paths(&config_path, &template_paths, defaults, &destination_path);
+            (Box::new(build), inputs)
         }
+        _ => return Err(Error::Usage(USAGE.to_string())),
     };
 
-    tera.register_filter("linebreaks", linebreaks);
+    if check_only {
+        let outputs = build()?;
+        if !output::check(&outputs)? {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    if dry_run {
+        let outputs = build()?;
+        return output::dry_run(&outputs, force);
+    }
 
-    let files =
-        get_file_list(&template_path, &template_glob).expect("failed to get files from glob");
+    if !watch {
+        let outputs = build()?;
+        output::print(&output::write(&outputs, force)?);
+        return Ok(());
+    }
 
-    println!("{:?}", &files);
+    let build_and_write = || {
+        let outputs = build()?;
+        let changes = output::write(&outputs, force)?;
+        let summary = output::summary(&changes);
+        // only the files that need attention, the summary covers the rest
+        let edited: Vec<_> = changes
+            .into_iter()
+            .filter(|change| change.status == output::Status::Edited)
+            .collect();
+        output::print(&edited);
+        Ok(summary)
+    };
+    // a broken template or config should not prevent watching, it can be fixed while we wait
+    match build_and_write() {
+        Ok(summary) => println!("{}", summary),
+        Err(e) => eprintln!("error: {}", e),
+    }
+    watch::watch(&inputs, build_and_write)
+}
 
-    write(&config_path, &destination_path, &tera, &files);
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    }
+}
 
-    if watch {
-        let copy_of_config_path = config_path.clone();
-        // Automatically select the best implementation for your platform.
-        let mut watcher = notify::recommended_watcher(move |res| match res {
-            Ok(event) => {
-                println!("event: {:?}", event);
-                _ = tera.full_reload();
-                write(&copy_of_config_path, &destination_path, &tera, &files);
-            }
-            Err(e) => println!("watch error: {:?}", e),
-        })?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use manifest::MANIFEST_FILE;
+    use render::DEPLOY_FILE;
 
-        // Add a path to be watched. All files and directories at that path and
-        // below will be monitored for changes.
-        watcher.watch(Path::new(template_path.as_str()), RecursiveMode::Recursive)?;
-        watcher.watch(Path::new(config_path.as_str()), RecursiveMode::NonRecursive)?;
+    /// A project building `config.yml` with the spawner, the manifest ending with `extra`
+    fn project(name: &str, max_writes: usize, extra: &str) -> Manifest {
+        let root = std::env::temp_dir().join(format!("pray-main-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let crate_root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        fs::copy(crate_root.join("config.yml"), root.join("config.yml")).unwrap();
+        let toml = format!(
+            "config = \"config.yml\"\n\n\
+             [budget]\nmax_writes = {}\n\n\
+             [targets.cairo]\ntemplates = [{:?}]\noutput = \"out\"\n\n{}",
+            max_writes,
+            crate_root.join("templates"),
+            extra
+        );
+        let path = root.join(MANIFEST_FILE);
+        fs::write(&path, toml).unwrap();
+        Manifest::load(&path).unwrap()
+    }
+
+    #[test]
+    fn budget_applies_to_each_batch() {
+        let config = load_config(Path::new("config.yml")).unwrap();
+        let setup = stats::stats(&config, None)[0]
+            .functions
+            .iter()
+            .find(|function| function.name == "setup")
+            .unwrap()
+            .writes;
+
+        let single = project("single-setup", setup - 1, "");
+        assert!(matches!(
+            build_manifest(&single, None),
+            Err(Error::Budget(_))
+        ));
+        fs::remove_dir_all(&single.root).unwrap();
 
-        println!("watching...");
-        io::stdin().read_line(&mut String::new()).unwrap();
+        let batched = project(
+            "batched",
+            setup - 1,
+            "[batches]\nby = \"rooms\"\nrooms = 1\n",
+        );
+        let outputs = build_manifest(&batched, None).unwrap();
+        let deploy = outputs
+            .iter()
+            .find(|output| output.path.ends_with(DEPLOY_FILE))
+            .unwrap();
+        let deploy: serde_json::Value = serde_json::from_str(&deploy.content).unwrap();
+        assert_eq!(deploy["batches"][0]["entrypoint"], "setup_batch_0");
+        fs::remove_dir_all(&batched.root).unwrap();
     }
 
-    Ok(())
+    #[test]
+    fn json_keeps_ids_above_2_53() {
+        let old = load_config(Path::new("fixtures/world.yml")).unwrap();
+        let mut new = load_config(Path::new("fixtures/world.yml")).unwrap();
+        new.levels[0].rooms[0].room_name = "Big barn".to_string();
+        let room_id = new.levels[0].rooms[0].room_id;
+        assert!(room_id > 1 << 53);
+
+        let diff = pray_engine::diff::diff(&old, &new);
+        let json: serde_json::Value = serde_json::from_str(&to_json(&diff, &[])).unwrap();
+        let id = json["rooms"][0]["roomID"].as_str().unwrap();
+        assert_eq!(id.parse::<u64>().unwrap(), room_id);
+    }
 }
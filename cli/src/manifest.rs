@@ -0,0 +1,222 @@
+use crate::builtin;
+use crate::error::{Error, Result};
+use crate::render::load;
+use pray_engine::merge;
+use pray_engine::partition::Partition;
+use pray_engine::stats::Budget;
+use serde::{Deserialize, Deserializer};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tera::{Context, Tera, Value};
+
+pub const MANIFEST_FILE: &str = "pray.toml";
+
+/// `pray.toml`, describes where a project keeps its world and what to generate from it.
+/// Paths are relative to the folder containing the manifest.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    /// Folder, or list of folders, of room markdown files. When set the config is regenerated
+    /// from them on every build
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub markdown: Vec<PathBuf>,
+    /// The world config fed to the templates
+    pub config: PathBuf,
+    /// Partial configs merged into the world, e.g. areas owned by different writers
+    #[serde(default)]
+    pub include: Vec<PathBuf>,
+    /// YAML files exposed to the templates as `vocabulary.<file stem>`
+    #[serde(default)]
+    pub vocabulary: Vec<PathBuf>,
+    /// Extra variables for every template
+    #[serde(default)]
+    pub context: BTreeMap<String, Value>,
+    pub targets: BTreeMap<String, Target>,
+    /// Template folders searched after those of each target, e.g. macros shared by every target
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub templates: Vec<PathBuf>,
+    /// Limits the world has to stay within for the build to succeed, see `pray stats`
+    #[serde(default)]
+    pub budget: Budget,
+    /// How the rooms are grouped into batches, each set up by its own entrypoint. Unset,
+    /// there are no batches and the spawner sets the world up with a single `setup`
+    pub batches: Option<Partition>,
+    /// Configs, or folders of configs, rendered by `pray test` and compared with their snapshots
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub fixtures: Vec<PathBuf>,
+    #[serde(skip)]
+    pub root: PathBuf,
+}
+
+/// A folder of templates and where to write what they render
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Target {
+    /// Template folders, searched in order before those of the project. Their templates
+    /// override those of the `defaults` set by name, or add to them
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub templates: Vec<PathBuf>,
+    /// Template set shipped with pray the target starts from, see `builtin`,
+    /// the set named like the target when not set
+    pub defaults: Option<String>,
+    pub output: PathBuf,
+    /// Extra variables for this target, overriding the project wide ones
+    #[serde(default)]
+    pub context: BTreeMap<String, Value>,
+}
+
+/// `"a"` or `["a", "b"]`
+fn one_or_many<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Vec<PathBuf>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(PathBuf),
+        Many(Vec<PathBuf>),
+    }
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(path) => vec![path],
+        OneOrMany::Many(paths) => paths,
+    })
+}
+
+impl Manifest {
+    /// Find `pray.toml` in the current folder or one of its parents
+    pub fn find() -> Result<PathBuf> {
+        let cwd = std::env::current_dir().map_err(|e| Error::Io(PathBuf::from("."), e))?;
+
+        // keep the path relative so the reported file names stay short
+        let mut relative = PathBuf::new();
+        for dir in cwd.ancestors() {
+            if dir.join(MANIFEST_FILE).is_file() {
+                return Ok(relative.join(MANIFEST_FILE));
+            }
+            relative.push("..");
+        }
+        Err(Error::Manifest(
+            PathBuf::from(MANIFEST_FILE),
+            "not found in the current folder or its parents".to_string(),
+        ))
+    }
+
+    pub fn load(path: &Path) -> Result<Manifest> {
+        let str = fs::read_to_string(path).map_err(|e| Error::Io(path.to_path_buf(), e))?;
+        let mut manifest: Manifest =
+            toml::from_str(&str).map_err(|e| Error::Manifest(path.to_path_buf(), e.to_string()))?;
+        manifest.root = path.parent().unwrap_or(Path::new("")).to_path_buf();
+        Ok(manifest)
+    }
+
+    /// Resolve a path relative to the manifest
+    pub fn path(&self, path: &Path) -> PathBuf {
+        self.root.join(path)
+    }
+
+    /// The targets to build: the named one, or all of them
+    pub fn targets(&self, name: Option<&str>) -> Result<Vec<(&String, &Target)>> {
+        match name {
+            None => Ok(self.targets.iter().collect()),
+            Some(name) => match self.targets.get_key_value(name) {
+                Some(target) => Ok(vec![target]),
+                None => Err(Error::Manifest(
+                    self.path(Path::new(MANIFEST_FILE)),
+                    format!(
+                        "unknown target `{}`, expected one of: {}",
+                        name,
+                        self.targets.keys().cloned().collect::<Vec<_>>().join(", ")
+                    ),
+                )),
+            },
+        }
+    }
+
+    /// The templates of the target `name`, its folders and those of the project over its default set
+    pub fn templates(&self, name: &str, target: &Target) -> Result<(Tera, Vec<String>)> {
+        let path = self.path(Path::new(MANIFEST_FILE));
+        let defaults = match &target.defaults {
+            Some(set) => Some(builtin::find(set).ok_or_else(|| {
+                Error::Manifest(
+                    path.clone(),
+                    format!(
+                        "target `{}` starts from an unknown template set `{}`, the built-in sets are:\n{}",
+                        name,
+                        set,
+                        builtin::list()
+                    ),
+                )
+            })?),
+            None => builtin::find(name),
+        };
+        if defaults.is_none() && target.templates.is_empty() {
+            return Err(Error::Manifest(
+                path,
+                format!(
+                    "target `{}` has no templates folder and is not one of the built-in template sets:\n{}",
+                    name,
+                    builtin::list()
+                ),
+            ));
+        }
+        let template_paths: Vec<PathBuf> = target
+            .templates
+            .iter()
+            .chain(&self.templates)
+            .map(|templates| self.path(templates))
+            .collect();
+        load(defaults, &template_paths)
+    }
+
+    /// Variables added to the config in the templates of `target`
+    pub fn context(&self, target: &Target) -> Result<Context> {
+        let mut context = Context::new();
+        for (key, value) in &self.context {
+            context.insert(key, value);
+        }
+
+        let mut vocabulary = BTreeMap::new();
+        for path in &self.vocabulary {
+            let path = self.path(path);
+            let str = fs::read_to_string(&path).map_err(|e| Error::Io(path.clone(), e))?;
+            let value: Value = serde_yml::from_str(&str)
+                .map_err(|e| Error::Manifest(path.clone(), e.to_string()))?;
+            let name = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            vocabulary.insert(name, value);
+        }
+        if !vocabulary.is_empty() {
+            context.insert("vocabulary", &vocabulary);
+        }
+
+        for (key, value) in &target.context {
+            context.insert(key, value);
+        }
+        Ok(context)
+    }
+
+    /// Every file or folder a build reads, to be watched for changes
+    pub fn inputs(&self) -> Vec<PathBuf> {
+        let mut inputs = Vec::new();
+        // with the configs they include, as they are when watching starts
+        if self.markdown.is_empty() {
+            inputs.extend(merge::files(&self.path(&self.config)));
+        }
+        // the config is written from the markdown, watching it would rebuild twice
+        inputs.extend(self.markdown.iter().map(|path| self.path(path)));
+        for include in &self.include {
+            inputs.extend(merge::files(&self.path(include)));
+        }
+        inputs.extend(self.vocabulary.iter().map(|path| self.path(path)));
+        inputs.extend(
+            self.targets
+                .values()
+                .flat_map(|target| &target.templates)
+                .chain(&self.templates)
+                .map(|templates| self.path(templates)),
+        );
+        inputs
+    }
+}
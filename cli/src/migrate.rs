@@ -0,0 +1,132 @@
+use crate::error::{Error, Result};
+use pray_engine::format::Format;
+use pray_engine::migrate::{self, VERSION};
+use pray_engine::Config;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The configs `value` includes, relative to the folder of `path`
+fn includes(path: &Path, value: &Value) -> Vec<PathBuf> {
+    let parent = path.parent().unwrap_or(Path::new(""));
+    let includes = value.get("include").and_then(Value::as_array);
+    includes
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .map(|include| parent.join(include))
+        .collect()
+}
+
+/// Upgrade config files written for older versions in place, and the configs they include,
+/// printing what changed
+pub fn migrate(paths: &[PathBuf], dry_run: bool) -> Result<()> {
+    let mut pending: Vec<PathBuf> = paths.iter().rev().cloned().collect();
+    let mut done: Vec<PathBuf> = Vec::new();
+    while let Some(path) = pending.pop() {
+        let path = &path;
+        // configs can include each other, loading them reports the cycle
+        let identity = path.canonicalize().unwrap_or_else(|_| path.clone());
+        if done.contains(&identity) {
+            continue;
+        }
+        done.push(identity);
+
+        let format = Format::from_path(path).map_err(|e| Error::config(path, e))?;
+        let str = fs::read_to_string(path).map_err(|e| Error::Io(path.clone(), e))?;
+        let mut value: Value = format
+            .deserialize(&str)
+            .map_err(|e| Error::config(path, e))?;
+
+        let from = migrate::version(&value);
+        let changes = migrate::migrate(&mut value).map_err(|e| Error::config(path, e))?;
+        // the first include is migrated next
+        pending.extend(includes(path, &value).into_iter().rev());
+        if changes.is_empty() {
+            println!(
+                "{} is up to date (version {})",
+                path.to_string_lossy(),
+                from
+            );
+            continue;
+        }
+
+        // read it back so a migration can not write a config that does not load
+        let config: Config = serde_json::from_value(value)
+            .map_err(|e| Error::Config(path.clone(), e.to_string()))?;
+        let verb = if dry_run { "would migrate" } else { "migrated" };
+        println!(
+            "{} {} from version {} to {}",
+            verb,
+            path.to_string_lossy(),
+            from,
+            VERSION
+        );
+        for change in &changes {
+            println!("  {}", change);
+        }
+        if !dry_run {
+            fs::write(path, format.serialize(&config)).map_err(|e| Error::Io(path.clone(), e))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pray_engine::merge;
+
+    #[test]
+    fn migrates_included_configs() {
+        let dir = std::env::temp_dir().join(format!("pray-migrate-{}", std::process::id()));
+        fs::create_dir_all(dir.join("areas")).unwrap();
+        fs::write(
+            dir.join("config.yml"),
+            "include: [areas/box.yml]\nlevels: []\n",
+        )
+        .unwrap();
+        let part = "
+levels:
+- levelName: test
+  rooms:
+  - roomID: 1
+    roomName: room
+    roomDescription: a room
+    roomType: Plain
+    biomeType: Prarie
+    objectIds: [2]
+    dirObjIds: []
+    objects:
+    - objID: 2
+      type: Box
+      material: Wood
+      objDescription: a box
+      direction: null
+      destination: null
+      actions:
+      - actionID: 3
+        type: Open
+        enabled: true
+        revertable: false
+        dBitText: it opens
+        dBit: false
+        affectsAction:
+          actionID: 3
+          objectID: 2
+";
+        fs::write(dir.join("areas/box.yml"), part).unwrap();
+
+        migrate(&[dir.join("config.yml")], false).unwrap();
+        for file in ["config.yml", "areas/box.yml"] {
+            let value: Value = Format::Yaml
+                .deserialize(&fs::read_to_string(dir.join(file)).unwrap())
+                .unwrap();
+            assert_eq!(migrate::version(&value), VERSION, "{}", file);
+        }
+        let config = merge::load(&dir.join("config.yml")).unwrap();
+        assert_eq!(config.levels[0].rooms.len(), 1);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
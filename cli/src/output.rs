@@ -0,0 +1,399 @@
+use crate::error::{Error, Result};
+use crate::generated::{hash, relative, Entry, Generated};
+use similar::{ChangeTag, TextDiff};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+
+/// A rendered template and the file it is destined for
+pub struct Output {
+    pub path: PathBuf,
+    pub content: String,
+    /// Folder whose record of generated files lists this output
+    pub root: PathBuf,
+    /// Name the output is recorded under, the manifest target or `default`
+    pub target: String,
+    /// Template, or markdown folder, the output is generated from
+    pub template: String,
+    pub config: String,
+}
+
+/// What a build does to a file
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Status {
+    Created,
+    Changed,
+    Unchanged,
+    /// Generated by a previous build but not anymore
+    Removed,
+    /// Edited by hand since it was generated, left alone unless forced
+    Edited,
+}
+
+impl Status {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Status::Created => "created",
+            Status::Changed => "changed",
+            Status::Unchanged => "unchanged",
+            Status::Removed => "removed",
+            Status::Edited => "edited",
+        }
+    }
+}
+
+/// A file touched by a build, `output` is `None` for files that are not generated anymore
+pub struct Change<'a> {
+    pub path: PathBuf,
+    pub status: Status,
+    pub output: Option<&'a Output>,
+}
+
+impl Change<'_> {
+    /// Explanation printed below the status line
+    pub fn note(&self) -> Option<&'static str> {
+        match (self.status, self.output) {
+            (Status::Edited, Some(_)) => {
+                Some("edited since it was generated, use --force to overwrite it")
+            }
+            (Status::Edited, None) => {
+                Some("no longer generated but edited since, use --force to remove it")
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Output {
+    /// Content of the destination file, if it exists
+    pub fn current(&self) -> Option<String> {
+        fs::read_to_string(&self.path).ok()
+    }
+
+    /// Print a unified diff between the file on disk and the rendered content
+    pub fn print_diff(&self) {
+        let current = self.current().unwrap_or_default();
+        let path = self.path.to_string_lossy();
+        let diff = TextDiff::from_lines(current.as_str(), self.content.as_str());
+
+        if !std::io::stdout().is_terminal() {
+            print!("{}", diff.unified_diff().header(&path, "generated"));
+            return;
+        }
+
+        println!("\x1b[1m--- {}\n+++ generated\x1b[0m", path);
+        for hunk in diff.unified_diff().iter_hunks() {
+            println!("\x1b[36m{}\x1b[0m", hunk.header());
+            for change in hunk.iter_changes() {
+                let (sign, color) = match change.tag() {
+                    ChangeTag::Delete => ("-", "\x1b[31m"),
+                    ChangeTag::Insert => ("+", "\x1b[32m"),
+                    ChangeTag::Equal => (" ", ""),
+                };
+                print!("{}{}{}\x1b[0m", color, sign, change);
+                if change.missing_newline() {
+                    println!();
+                }
+            }
+        }
+    }
+}
+
+/// Records of generated files for every folder the outputs are written to
+fn load_records(outputs: &[Output]) -> Result<BTreeMap<&Path, Generated>> {
+    let mut records = BTreeMap::new();
+    for output in outputs {
+        if !records.contains_key(output.root.as_path()) {
+            records.insert(output.root.as_path(), Generated::load(&output.root)?);
+        }
+    }
+    Ok(records)
+}
+
+/// Work out what writing the outputs would do, without touching the disk
+pub fn plan(outputs: &[Output], force: bool) -> Result<Vec<Change<'_>>> {
+    let records = load_records(outputs)?;
+
+    let mut changes = Vec::new();
+    for output in outputs {
+        let record = &records[output.root.as_path()];
+        let status = match output.current() {
+            None => Status::Created,
+            Some(current) if current == output.content => Status::Unchanged,
+            Some(current) => match record.entry(&output.root, &output.target, &output.path) {
+                Some(entry) if entry.hash != hash(&current) && !force => Status::Edited,
+                _ => Status::Changed,
+            },
+        };
+        changes.push(Change {
+            path: output.path.clone(),
+            status,
+            output: Some(output),
+        });
+    }
+
+    // files generated by a previous build of the same targets that are not produced anymore
+    let produced: BTreeSet<&Path> = outputs.iter().map(|output| output.path.as_path()).collect();
+    for (root, record) in &records {
+        let targets: BTreeSet<&str> = outputs
+            .iter()
+            .filter(|output| output.root == *root)
+            .map(|output| output.target.as_str())
+            .collect();
+        for target in targets {
+            for (file, entry) in record.targets.get(target).into_iter().flatten() {
+                let path = root.join(file);
+                if produced.contains(path.as_path()) {
+                    continue;
+                }
+                let status = match fs::read_to_string(&path) {
+                    Err(_) => continue,
+                    Ok(current) if force || hash(&current) == entry.hash => Status::Removed,
+                    Ok(_) => Status::Edited,
+                };
+                changes.push(Change {
+                    path,
+                    status,
+                    output: None,
+                });
+            }
+        }
+    }
+    Ok(changes)
+}
+
+/// Write the outputs whose content differs from the files on disk and remove the files
+/// that are not generated anymore. Untouched files keep their modification time so
+/// downstream builds are not triggered, hand edited files are kept unless `force` is set.
+pub fn write(outputs: &[Output], force: bool) -> Result<Vec<Change<'_>>> {
+    let changes = plan(outputs, force)?;
+    for change in &changes {
+        match (change.status, change.output) {
+            (Status::Created | Status::Changed, Some(output)) => {
+                if let Some(parent) = output.path.parent() {
+                    fs::create_dir_all(parent).map_err(|e| Error::Io(parent.to_path_buf(), e))?;
+                }
+                fs::write(&output.path, &output.content)
+                    .map_err(|e| Error::Io(output.path.clone(), e))?;
+            }
+            (Status::Removed, _) => {
+                fs::remove_file(&change.path).map_err(|e| Error::Io(change.path.clone(), e))?;
+                // drop the folder too when it was only holding generated files, fails when it is not empty
+                if let Some(parent) = change.path.parent() {
+                    let _ = fs::remove_dir(parent);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for (root, previous) in load_records(outputs)? {
+        let mut record = Generated::default();
+        for (target, files) in &previous.targets {
+            record.targets.insert(target.clone(), files.clone());
+        }
+
+        let built: BTreeSet<&str> = outputs
+            .iter()
+            .filter(|output| output.root == root)
+            .map(|output| output.target.as_str())
+            .collect();
+        for target in built {
+            let files = record.targets.entry(target.to_string()).or_default();
+            files.clear();
+            for change in &changes {
+                let key = relative(root, &change.path);
+                let previous_entry = previous.entry(root, target, &change.path);
+                match (change.status, change.output) {
+                    // keep flagging hand edited files until they are dealt with
+                    (Status::Edited, _) => {
+                        if let Some(entry) = previous_entry {
+                            files.insert(key, entry.clone());
+                        }
+                    }
+                    (_, Some(output)) if output.root == root && output.target == target => {
+                        files.insert(
+                            key,
+                            Entry {
+                                hash: hash(&output.content),
+                                template: output.template.clone(),
+                                config: output.config.clone(),
+                            },
+                        );
+                    }
+                    _ => {}
+                }
+            }
+        }
+        record.targets.retain(|_, files| !files.is_empty());
+
+        if record != previous {
+            let path = Generated::path(root);
+            fs::create_dir_all(root).map_err(|e| Error::Io(root.to_path_buf(), e))?;
+            fs::write(&path, record.serialize()).map_err(|e| Error::Io(path, e))?;
+        }
+    }
+    Ok(changes)
+}
+
+/// One line summary of a build, e.g. `3 files: 1 created, 2 unchanged`
+pub fn summary(changes: &[Change]) -> String {
+    let counts: Vec<String> = [
+        Status::Created,
+        Status::Changed,
+        Status::Unchanged,
+        Status::Removed,
+        Status::Edited,
+    ]
+    .iter()
+    .filter_map(|status| {
+        let count = changes.iter().filter(|c| c.status == *status).count();
+        (count > 0).then(|| format!("{} {}", count, status.label()))
+    })
+    .collect();
+    format!("{} files: {}", changes.len(), counts.join(", "))
+}
+
+/// Print the status of every change and its note
+pub fn print(changes: &[Change]) {
+    for change in changes {
+        println!(
+            "{} {}",
+            change.status.label(),
+            change.path.to_string_lossy()
+        );
+        if let Some(note) = change.note() {
+            println!("  {}", note);
+        }
+    }
+}
+
+/// Print what `write` would do, with a diff for every file that would be created or changed
+pub fn dry_run(outputs: &[Output], force: bool) -> Result<()> {
+    for change in plan(outputs, force)? {
+        print(std::slice::from_ref(&change));
+        if let (Status::Created | Status::Changed, Some(output)) = (change.status, change.output) {
+            output.print_diff();
+        }
+    }
+    Ok(())
+}
+
+/// Compare the rendered outputs with the files on disk, printing a diff for each stale file
+pub fn check(outputs: &[Output]) -> Result<bool> {
+    let mut up_to_date = true;
+    for change in plan(outputs, false)? {
+        let path = change.path.to_string_lossy();
+        match (change.status, change.output) {
+            (Status::Unchanged, _) => println!("{} is up to date", path),
+            (_, Some(output)) => {
+                up_to_date = false;
+                println!("{} is stale", path);
+                output.print_diff();
+            }
+            (_, None) => {
+                up_to_date = false;
+                println!("{} is not generated anymore and should be removed", path);
+            }
+        }
+    }
+    Ok(up_to_date)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(root: &Path, file: &str, content: &str) -> Output {
+        Output {
+            path: root.join(file),
+            content: content.to_string(),
+            root: root.to_path_buf(),
+            target: "default".to_string(),
+            template: format!("{}.tera", file),
+            config: "config.yml".to_string(),
+        }
+    }
+
+    fn statuses(changes: &[Change]) -> Vec<(String, Status)> {
+        changes
+            .iter()
+            .map(|change| {
+                let file = change.path.file_name().unwrap().to_string_lossy();
+                (file.into_owned(), change.status)
+            })
+            .collect()
+    }
+
+    fn folder(name: &str) -> PathBuf {
+        let root =
+            std::env::temp_dir().join(format!("pray-output-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        root
+    }
+
+    #[test]
+    fn keeps_hand_edited_files_unless_forced() {
+        let root = folder("edited");
+        let first = [output(&root, "a.txt", "one")];
+        write(&first, false).unwrap();
+        fs::write(root.join("a.txt"), "edited").unwrap();
+
+        let second = [output(&root, "a.txt", "two")];
+        let changes = write(&second, false).unwrap();
+        assert_eq!(statuses(&changes), [("a.txt".into(), Status::Edited)]);
+        assert_eq!(fs::read_to_string(root.join("a.txt")).unwrap(), "edited");
+        // still flagged on the next build
+        let changes = plan(&second, false).unwrap();
+        assert_eq!(statuses(&changes), [("a.txt".into(), Status::Edited)]);
+
+        let changes = write(&second, true).unwrap();
+        assert_eq!(statuses(&changes), [("a.txt".into(), Status::Changed)]);
+        assert_eq!(fs::read_to_string(root.join("a.txt")).unwrap(), "two");
+        let changes = plan(&second, false).unwrap();
+        assert_eq!(statuses(&changes), [("a.txt".into(), Status::Unchanged)]);
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn removes_files_not_generated_anymore() {
+        let root = folder("stale");
+        let first = [
+            output(&root, "a.txt", "a"),
+            output(&root, "b.txt", "b"),
+            output(&root, "c.txt", "c"),
+        ];
+        write(&first, false).unwrap();
+        fs::write(root.join("c.txt"), "edited").unwrap();
+
+        let second = [output(&root, "a.txt", "a")];
+        let changes = write(&second, false).unwrap();
+        assert_eq!(
+            statuses(&changes),
+            [
+                ("a.txt".into(), Status::Unchanged),
+                ("b.txt".into(), Status::Removed),
+                ("c.txt".into(), Status::Edited),
+            ]
+        );
+        assert!(!root.join("b.txt").exists());
+        assert!(root.join("c.txt").exists());
+
+        let changes = write(&second, true).unwrap();
+        assert_eq!(
+            statuses(&changes),
+            [
+                ("a.txt".into(), Status::Unchanged),
+                ("c.txt".into(), Status::Removed),
+            ]
+        );
+        assert!(!root.join("c.txt").exists());
+        let record = Generated::load(&root).unwrap();
+        let files: Vec<&String> = record.targets["default"].keys().collect();
+        assert_eq!(files, ["a.txt"]);
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
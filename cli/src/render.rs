@@ -0,0 +1,471 @@
+use crate::builtin::{self, Set};
+use crate::diagnose;
+use crate::error::{Error, Result};
+use crate::filters::{linebreaks, rust_string, slug, string_ids};
+use crate::generated::relative;
+use crate::output::Output;
+use glob::glob;
+use pray_engine::partition::{self, Batch, Partition};
+use pray_engine::{locale, merge, text, Config, Level};
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use tera::{Context, Tera};
+
+/// Batches in call order with their entrypoint and the files generated for each, written next to them
+pub const DEPLOY_FILE: &str = "deploy.json";
+
+fn get_file_list(parent_dir: &Path, pattern: &str) -> Vec<String> {
+    let mut file_list = Vec::new();
+
+    for entry in glob(pattern).expect("invalid pattern") {
+        match entry {
+            Ok(path) => {
+                if let Ok(relative_path) = path.strip_prefix(parent_dir) {
+                    file_list.push(relative_path.to_string_lossy().into_owned());
+                }
+            }
+            Err(e) => eprintln!("Error: {:?}", e),
+        }
+    }
+
+    file_list
+}
+
+pub fn register_filters(tera: &mut Tera) {
+    tera.register_filter("linebreaks", linebreaks);
+    tera.register_filter("slug", slug);
+    tera.register_filter("rust_string", rust_string);
+    tera.register_filter("string_ids", string_ids);
+}
+
+/// Templates whose file name starts with `_` hold macros for other templates, they are not rendered themselves
+fn is_partial(file: &str) -> bool {
+    Path::new(file)
+        .file_name()
+        .is_some_and(|name| name.to_string_lossy().starts_with('_'))
+}
+
+/// Load the standard macros, the templates of the built-in `set`, then every template below
+/// `template_paths` over them. A template of a folder replaces the template with the same name
+/// of the set and of the folders after it, so they can import, include or extend each other.
+/// The folders are globbed on each call so templates added or removed while watching are picked up.
+/// Also returns the templates to render, partials and standard macros left out.
+pub fn load(set: Option<&Set>, template_paths: &[PathBuf]) -> Result<(Tera, Vec<String>)> {
+    let mut tera = Tera::default();
+    tera.add_raw_templates(builtin::STD.iter().copied())?;
+    let mut files: Vec<String> = Vec::new();
+    if let Some(set) = set {
+        tera.add_raw_templates(set.templates.iter().copied())?;
+        files.extend(set.templates.iter().map(|(name, _)| name.to_string()));
+    }
+    // every folder is added in one go, tera resolves `extends` when templates are added
+    // and a template can extend one of any other folder
+    let mut paths: Vec<(PathBuf, Option<String>)> = Vec::new();
+    for template_path in template_paths {
+        let template_glob = format!("{}/**/*.tera", template_path.to_string_lossy());
+        for file in get_file_list(template_path, &template_glob) {
+            // the first folder wins
+            if paths.iter().any(|(_, name)| name.as_ref() == Some(&file)) {
+                continue;
+            }
+            if !files.contains(&file) {
+                files.push(file.clone());
+            }
+            paths.push((template_path.join(&file), Some(file)));
+        }
+    }
+    tera.add_template_files(paths)?;
+    register_filters(&mut tera);
+
+    let std: Vec<&str> = builtin::STD.iter().map(|(name, _)| *name).collect();
+    files.retain(|file| !is_partial(file) && !std.contains(&file.as_str()));
+    Ok((tera, files))
+}
+
+/// Load the template folders over the `defaults` set. The first folder can also be the name
+/// of a built-in set, when there is no such folder, the other folders going over that set.
+pub fn load_templates_or_builtin(
+    template_paths: &[PathBuf],
+    defaults: Option<&Set>,
+) -> Result<(Tera, Vec<String>)> {
+    let Some((first, others)) = template_paths.split_first() else {
+        return load(defaults, &[]);
+    };
+    if first.is_dir() {
+        return load(defaults, template_paths);
+    }
+    match first.to_str().and_then(builtin::find) {
+        Some(set) => load(Some(set), others),
+        None => Err(Error::Io(
+            first.clone(),
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!(
+                    "not a templates folder nor one of the built-in template sets:\n{}",
+                    builtin::list()
+                ),
+            ),
+        )),
+    }
+}
+
+/// Whether a rendered file name stays inside the target folder: relative and without `..`
+fn is_inside(name: &str) -> bool {
+    Path::new(name)
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+}
+
+/// How many times a template is rendered, decided by the variables used in its file name
+#[derive(Debug, PartialEq, Eq)]
+enum Scope {
+    Once,
+    Locale,
+    Level,
+    Batch,
+    Room,
+}
+
+impl Scope {
+    /// `rooms/{{room.roomName | slug}}.cairo.tera` is rendered once per room,
+    /// `levels/{{level.levelName}}/setup.cairo.tera` once per level,
+    /// `batches/{{batch.name}}.cairo.tera` once per batch,
+    /// `texts/{{locale}}.cairo.tera` once per locale and anything else once
+    fn of(file: &str) -> Scope {
+        let identifiers: Vec<&str> = file
+            .split("{{")
+            .skip(1)
+            .filter_map(|part| part.split("}}").next())
+            .flat_map(|expression| {
+                expression.split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.'))
+            })
+            .filter_map(|token| token.split('.').next())
+            .collect();
+
+        if identifiers.contains(&"room") {
+            Scope::Room
+        } else if identifiers.contains(&"batch") {
+            Scope::Batch
+        } else if identifiers.contains(&"level") {
+            Scope::Level
+        } else if identifiers.contains(&"locale") {
+            Scope::Locale
+        } else {
+            Scope::Once
+        }
+    }
+}
+
+/// The contexts a template is rendered with, each with a description used in error messages
+fn instances(
+    file: &str,
+    config: &Config,
+    batches: &[Batch],
+    context: &Context,
+) -> Vec<(String, Context)> {
+    match Scope::of(file) {
+        Scope::Once => vec![(file.to_string(), context.clone())],
+        Scope::Locale => locale::locales(config)
+            .into_iter()
+            .map(|locale| {
+                let mut context = context.clone();
+                context.insert("locale", locale);
+                (format!("{} for locale {}", file, locale), context)
+            })
+            .collect(),
+        Scope::Level => config
+            .levels
+            .iter()
+            .map(|level| {
+                let mut context = context.clone();
+                context.insert("level", level);
+                (format!("{} for level {}", file, level.level_name), context)
+            })
+            .collect(),
+        Scope::Batch => batches
+            .iter()
+            .map(|batch| {
+                let mut context = context.clone();
+                let level = config
+                    .levels
+                    .iter()
+                    .find(|level| level.level_name == batch.level_name)
+                    .expect("batch of an unknown level");
+                let rooms: Vec<_> = batch
+                    .room_ids
+                    .iter()
+                    .filter_map(|id| level.rooms.iter().find(|room| room.room_id == *id))
+                    .collect();
+                context.insert("level", level);
+                context.insert("batch", batch);
+                context.insert("rooms", &rooms);
+                (format!("{} for {}", file, batch.name), context)
+            })
+            .collect(),
+        Scope::Room => config
+            .levels
+            .iter()
+            .flat_map(|level| {
+                level.rooms.iter().map(move |room| {
+                    let mut context = context.clone();
+                    context.insert("level", level);
+                    context.insert("room", room);
+                    (format!("{} for room {}", file, room.room_name), context)
+                })
+            })
+            .collect(),
+    }
+}
+
+/// Load the config in the format matching its extension, with the configs it includes
+pub fn load_config(config_path: &Path) -> Result<Config> {
+    merge::load(config_path).map_err(|e| Error::config(config_path, e))
+}
+
+/// Render every template with the config, `extra` variables are added to the context.
+/// When a template is rendered per batch, `deploy.json` lists the batches in call order
+/// with the files generated for each.
+pub fn render(
+    config: &Config,
+    config_path: &Path,
+    partition: Option<&Partition>,
+    extra: &Context,
+    destination_path: &Path,
+    tera: &Tera,
+    files: &[String],
+) -> Result<Vec<Output>> {
+    let mut context = Context::from_serialize(config)?;
+    // every object with where it starts, rooms, containers and inventories alike
+    let objects: Vec<_> = config.levels.iter().flat_map(Level::objects).collect();
+    context.insert("objects", &objects);
+    // every text by locale then text ID, falling back to the source locale
+    let texts: BTreeMap<&str, _> = locale::locales(config)
+        .into_iter()
+        .map(|locale| (locale, locale::table(config, locale)))
+        .collect();
+    context.insert("texts", &texts);
+    // the same texts by locale, each distinct text once
+    let text_tables: BTreeMap<&str, _> = texts
+        .iter()
+        .map(|(locale, texts)| (*locale, text::table(texts)))
+        .collect();
+    context.insert("text_tables", &text_tables);
+    let batches = partition.map_or_else(Vec::new, |by| partition::partition(config, by));
+    context.insert("batches", &batches);
+    context.extend(extra.clone());
+
+    // file names are rendered with their own instance so they can use the same filters
+    let mut names = tera.clone();
+
+    let mut outputs = Vec::new();
+    let mut sources: HashMap<PathBuf, String> = HashMap::new();
+    // files generated for each batch, by batch name
+    let mut batch_files: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for file in files {
+        let destination_file_name = file
+            .strip_suffix(".tera")
+            .expect("failted to remove .tera extension to file name");
+
+        for (source, context) in instances(file, config, &batches, &context) {
+            let name = if destination_file_name.contains("{{") {
+                names.render_str(destination_file_name, &context)?
+            } else {
+                destination_file_name.to_string()
+            };
+            if !is_inside(&name) {
+                return Err(Error::OutsideTarget(name, source));
+            }
+            let path = destination_path.join(name);
+            if let Some(previous) = sources.insert(path.clone(), source.clone()) {
+                return Err(Error::Collision(path, previous, source));
+            }
+
+            let content = tera.render(file, &context).map_err(|e| {
+                Error::Render(Box::new(diagnose::explain(
+                    tera, file, &source, &context, e,
+                )))
+            })?;
+            if let Some(batch) = context.get("batch").and_then(|batch| batch.get("name")) {
+                let name = batch.as_str().unwrap_or_default().to_string();
+                let files = batch_files.entry(name).or_default();
+                files.push(relative(destination_path, &path));
+            }
+            outputs.push(Output {
+                path,
+                content,
+                root: destination_path.to_path_buf(),
+                target: "default".to_string(),
+                template: file.clone(),
+                config: config_path.to_string_lossy().into_owned(),
+            });
+        }
+    }
+
+    // a single batch is set up like an unpartitioned world, there is no order to follow
+    if batches.len() > 1 {
+        let path = destination_path.join(DEPLOY_FILE);
+        let source = "the deploy order".to_string();
+        if let Some(previous) = sources.insert(path.clone(), source.clone()) {
+            return Err(Error::Collision(path, previous, source));
+        }
+        let order: Vec<_> = batches
+            .iter()
+            .map(|batch| {
+                let mut entry = serde_json::to_value(batch).expect("failed to serialize batch");
+                let files = batch_files.remove(&batch.name).unwrap_or_default();
+                entry["entrypoint"] = batch.entrypoint().into();
+                entry["files"] = files.into();
+                entry
+            })
+            .collect();
+        let deploy = serde_json::json!({ "batches": order });
+        outputs.push(Output {
+            path,
+            content: serde_json::to_string_pretty(&deploy).expect("failed to serialize batches")
+                + "\n",
+            root: destination_path.to_path_buf(),
+            target: "default".to_string(),
+            template: source,
+            config: config_path.to_string_lossy().into_owned(),
+        });
+    }
+    Ok(outputs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// Template folders `a` and `b` in a fresh folder, each with its files
+    fn folders(name: &str, a: &[(&str, &str)], b: &[(&str, &str)]) -> (PathBuf, Vec<PathBuf>) {
+        let root = std::env::temp_dir().join(format!("pray-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let mut paths = Vec::new();
+        for (folder, templates) in [("a", a), ("b", b)] {
+            let path = root.join(folder);
+            fs::create_dir_all(&path).unwrap();
+            for (file, content) in templates {
+                fs::write(path.join(file), content).unwrap();
+            }
+            paths.push(path);
+        }
+        (root, paths)
+    }
+
+    const BASE: &str = "base {% block body %}{% endblock body %}";
+    const CHILD: &str = "{% extends \"_base.tera\" %}{% block body %}child{% endblock body %}";
+
+    #[test]
+    fn extends_templates_of_later_folders() {
+        let (root, paths) = folders(
+            "extends-later",
+            &[("child.txt.tera", CHILD)],
+            &[("_base.tera", BASE)],
+        );
+        let (tera, files) = load(None, &paths).unwrap();
+        assert_eq!(files, ["child.txt.tera"]);
+        assert_eq!(
+            tera.render("child.txt.tera", &Context::new()).unwrap(),
+            "base child"
+        );
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn extends_templates_of_earlier_folders() {
+        let (root, paths) = folders(
+            "extends-earlier",
+            &[("_base.tera", BASE)],
+            &[("child.txt.tera", CHILD)],
+        );
+        let (tera, files) = load(None, &paths).unwrap();
+        assert_eq!(files, ["child.txt.tera"]);
+        assert_eq!(
+            tera.render("child.txt.tera", &Context::new()).unwrap(),
+            "base child"
+        );
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn first_folder_wins() {
+        let (root, paths) = folders("first-wins", &[("x.txt.tera", "a")], &[("x.txt.tera", "b")]);
+        let (tera, files) = load(None, &paths).unwrap();
+        assert_eq!(files, ["x.txt.tera"]);
+        assert_eq!(tera.render("x.txt.tera", &Context::new()).unwrap(), "a");
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn scope_follows_the_variables_of_the_file_name() {
+        assert_eq!(Scope::of("world.json.tera"), Scope::Once);
+        assert_eq!(Scope::of("texts/{{locale}}.cairo.tera"), Scope::Locale);
+        assert_eq!(
+            Scope::of("{{ level.levelName }}/setup.cairo.tera"),
+            Scope::Level
+        );
+        assert_eq!(Scope::of("batches/{{batch.name}}.cairo.tera"), Scope::Batch);
+        assert_eq!(
+            Scope::of("{{level.levelName}}/{{ room.roomName | slug }}.cairo.tera"),
+            Scope::Room
+        );
+        // only whole variable names count
+        assert_eq!(Scope::of("{{ rooms | length }}.txt.tera"), Scope::Once);
+        assert_eq!(Scope::of("room.txt.tera"), Scope::Once);
+    }
+
+    #[test]
+    fn only_renders_inside_the_target_folder() {
+        assert!(is_inside("rooms/barn.cairo"));
+        assert!(is_inside("./barn.cairo"));
+        assert!(!is_inside("../barn.cairo"));
+        assert!(!is_inside("rooms/../../barn.cairo"));
+        assert!(!is_inside("/tmp/barn.cairo"));
+    }
+
+    fn render_rooms(config: &Config, file: &str) -> Result<Vec<Output>> {
+        let mut tera = Tera::default();
+        tera.add_raw_template(file, "{{ room.roomName }}").unwrap();
+        register_filters(&mut tera);
+        render(
+            config,
+            Path::new("world.yml"),
+            None,
+            &Context::new(),
+            Path::new("out"),
+            &tera,
+            &[file.to_string()],
+        )
+    }
+
+    #[test]
+    fn rejects_file_names_outside_the_target_folder() {
+        let config = load_config(Path::new("fixtures/world.yml")).unwrap();
+        let error = render_rooms(&config, "../{{ room.roomName | slug }}.txt.tera")
+            .err()
+            .unwrap();
+        assert!(
+            matches!(&error, Error::OutsideTarget(name, _) if name == "../barn.txt"),
+            "{}",
+            error
+        );
+        assert!(render_rooms(&config, "{{ room.roomName | slug }}.txt.tera").is_ok());
+    }
+
+    #[test]
+    fn rejects_rooms_with_the_same_slug() {
+        let mut config = load_config(Path::new("fixtures/world.yml")).unwrap();
+        config.levels[0].rooms[1].room_name = "barn!".to_string();
+        let error = render_rooms(&config, "{{ room.roomName | slug }}.txt.tera")
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "out/barn.txt is generated by both {{ room.roomName | slug }}.txt.tera for room Barn \
+             and {{ room.roomName | slug }}.txt.tera for room barn!"
+        );
+    }
+}
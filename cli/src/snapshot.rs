@@ -0,0 +1,265 @@
+//! Snapshot tests of templates: fixture configs are rendered in memory and compared with
+//! the files stored in `snapshots/<fixture name>/` next to each fixture.
+
+use crate::error::{Error, Result};
+use crate::output::Output;
+use crate::render::{load_config, render};
+use glob::glob;
+use pray_engine::format::Format;
+use pray_engine::partition::Partition;
+use similar::TextDiff;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tera::{Context, Tera};
+
+pub const SNAPSHOTS: &str = "snapshots";
+
+/// Set to rewrite the snapshots instead of comparing with them in `assert_snapshots`
+#[cfg(test)]
+pub const UPDATE_VAR: &str = "PRAY_UPDATE_SNAPSHOTS";
+
+/// The fixture configs given, a folder standing for every config file in it
+pub fn fixtures(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut fixtures = Vec::new();
+    for path in paths {
+        if !path.is_dir() {
+            fixtures.push(path.clone());
+            continue;
+        }
+        let entries = fs::read_dir(path).map_err(|e| Error::Io(path.clone(), e))?;
+        let mut configs: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.is_file() && Format::from_path(path).is_ok())
+            .collect();
+        configs.sort();
+        fixtures.extend(configs);
+    }
+    Ok(fixtures)
+}
+
+/// Folder the outputs of `fixture` are stored in
+pub fn folder(fixture: &Path) -> PathBuf {
+    let name = fixture.file_stem().unwrap_or_default();
+    fixture.with_file_name(SNAPSHOTS).join(name)
+}
+
+/// A snapshot that does not match what the templates render
+#[derive(Debug)]
+pub struct Mismatch {
+    pub path: PathBuf,
+    /// The stored snapshot, none when it is missing
+    pub expected: Option<String>,
+    /// What was rendered, none when the snapshot is not rendered anymore
+    pub actual: Option<String>,
+}
+
+impl Mismatch {
+    /// A unified diff from the snapshot to the rendered file
+    pub fn diff(&self) -> String {
+        let expected = self.expected.as_deref().unwrap_or_default();
+        let actual = self.actual.as_deref().unwrap_or_default();
+        let path = self.path.to_string_lossy();
+        TextDiff::from_lines(expected, actual)
+            .unified_diff()
+            .header(&path, "rendered")
+            .to_string()
+    }
+
+    pub fn summary(&self) -> String {
+        let path = self.path.to_string_lossy();
+        match (&self.expected, &self.actual) {
+            (None, _) => format!("{} has no snapshot", path),
+            (_, None) => format!("{} is not rendered anymore", path),
+            _ => format!("{} does not match its snapshot", path),
+        }
+    }
+}
+
+/// Every file below `folder`
+fn stored(folder: &Path) -> BTreeSet<PathBuf> {
+    let pattern = format!("{}/**/*", folder.to_string_lossy());
+    glob(&pattern)
+        .expect("invalid pattern")
+        .filter_map(|entry| entry.ok())
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+/// Compare outputs rendered into `folder` with the snapshots stored there
+pub fn compare(outputs: &[Output], folder: &Path) -> Vec<Mismatch> {
+    let mut stale = stored(folder);
+    let mut mismatches = Vec::new();
+    for output in outputs {
+        stale.remove(&output.path);
+        let expected = output.current();
+        if expected.as_deref() != Some(output.content.as_str()) {
+            mismatches.push(Mismatch {
+                path: output.path.clone(),
+                expected,
+                actual: Some(output.content.clone()),
+            });
+        }
+    }
+    for path in stale {
+        mismatches.push(Mismatch {
+            expected: fs::read_to_string(&path).ok(),
+            path,
+            actual: None,
+        });
+    }
+    mismatches
+}
+
+/// Make the snapshots match, writing what is rendered and removing what is not
+pub fn update(mismatches: &[Mismatch]) -> Result<()> {
+    for mismatch in mismatches {
+        let path = &mismatch.path;
+        match &mismatch.actual {
+            Some(content) => {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent).map_err(|e| Error::Io(parent.to_path_buf(), e))?;
+                }
+                fs::write(path, content).map_err(|e| Error::Io(path.clone(), e))?;
+            }
+            None => fs::remove_file(path).map_err(|e| Error::Io(path.clone(), e))?,
+        }
+    }
+    Ok(())
+}
+
+/// Render the templates for `fixture` into `folder`, where its snapshots are
+pub fn render_fixture(
+    fixture: &Path,
+    tera: &Tera,
+    files: &[String],
+    partition: Option<&Partition>,
+    context: &Context,
+    folder: &Path,
+) -> Result<Vec<Output>> {
+    let config = load_config(fixture)?;
+    render(&config, fixture, partition, context, folder, tera, files)
+}
+
+/// Print every mismatch and its diff
+pub fn print(mismatches: &[Mismatch]) {
+    for mismatch in mismatches {
+        println!("{}", mismatch.summary());
+        print!("{}", mismatch.diff());
+    }
+}
+
+/// Fail with the diff of every snapshot of `fixture` in `folder` that does not match the templates,
+/// or rewrite them when `PRAY_UPDATE_SNAPSHOTS` is set
+#[cfg(test)]
+pub fn assert_snapshots(fixture: &Path, tera: &Tera, files: &[String], folder: &Path) {
+    let outputs = render_fixture(fixture, tera, files, None, &Context::new(), folder)
+        .unwrap_or_else(|e| panic!("failed to render {}: {}", fixture.to_string_lossy(), e));
+    let mismatches = compare(&outputs, folder);
+    if std::env::var_os(UPDATE_VAR).is_some() {
+        update(&mismatches).expect("failed to update snapshots");
+        return;
+    }
+    if !mismatches.is_empty() {
+        let report: Vec<String> = mismatches
+            .iter()
+            .map(|mismatch| format!("{}\n{}", mismatch.summary(), mismatch.diff()))
+            .collect();
+        panic!(
+            "{}\nrun with {}=1 to accept the changes",
+            report.join("\n"),
+            UPDATE_VAR
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builtin::SETS;
+    use crate::render::load;
+
+    #[test]
+    fn builtin_sets_match_snapshots() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let paths = [root.join("config.yml"), root.join("fixtures")];
+        for fixture in fixtures(&paths).unwrap() {
+            for set in SETS {
+                let (tera, files) = load(Some(set), &[]).unwrap();
+                let folder = folder(&fixture).join(set.name);
+                assert_snapshots(&fixture, &tera, &files, &folder);
+            }
+        }
+    }
+
+    /// Every number JavaScript reads exactly, IDs have to be strings
+    fn assert_safe_numbers(value: &serde_json::Value) {
+        match value {
+            serde_json::Value::Number(number) => {
+                let safe = number.as_u64().is_some_and(|n| n < 1 << 53);
+                assert!(safe, "{} is not exact as a JavaScript number", number);
+            }
+            serde_json::Value::Array(values) => values.iter().for_each(assert_safe_numbers),
+            serde_json::Value::Object(map) => map.values().for_each(assert_safe_numbers),
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn json_set_is_safe_for_javascript() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let (tera, files) = load(crate::builtin::find("json"), &[]).unwrap();
+        for fixture in fixtures(&[root.join("config.yml"), root.join("fixtures")]).unwrap() {
+            let folder = folder(&fixture);
+            let config = load_config(&fixture).unwrap();
+            let outputs = render(
+                &config,
+                &fixture,
+                Some(&Partition::Level),
+                &Context::new(),
+                &folder,
+                &tera,
+                &files,
+            )
+            .unwrap();
+            for output in outputs
+                .iter()
+                .filter(|output| output.path.ends_with("world.json"))
+            {
+                assert_safe_numbers(&serde_json::from_str(&output.content).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn spawner_gives_each_player_their_own_inventory() {
+        let fixture = Path::new("fixtures/inventory.yml");
+        let (tera, files) = load(crate::builtin::find("cairo"), &[]).unwrap();
+        let config = load_config(fixture).unwrap();
+        let outputs = render(
+            &config,
+            fixture,
+            None,
+            &Context::new(),
+            Path::new("out"),
+            &tera,
+            &files,
+        )
+        .unwrap();
+        let spawner = &outputs[0].content;
+        assert!(spawner.contains("items: make_inventory(world, pid)"));
+
+        // players spawned one after the other store their objects under different IDs
+        let start = spawner.find("fn make_inventory").unwrap();
+        let end = start + spawner[start..].find("\n    }\n").unwrap();
+        let ids: Vec<&str> = spawner[start..end]
+            .lines()
+            .map(str::trim)
+            .filter(|line| line.starts_with("let object_id_") || line.starts_with("let action_id_"))
+            .collect();
+        assert_eq!(ids.len(), 3);
+        for id in ids {
+            assert!(id.ends_with(", pid);"), "{} is shared by every player", id);
+        }
+    }
+}
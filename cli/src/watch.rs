@@ -0,0 +1,119 @@
+use crate::error::{Error, Result};
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+/// How long the file system has to be quiet before rebuilding,
+/// a single save usually produces a burst of events
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+enum Message {
+    Event(Event),
+    Error(notify::Error),
+    Stop,
+}
+
+/// Editor swap and backup files, saving a file should not build twice
+fn is_temporary(path: &Path) -> bool {
+    path.file_name()
+        .map(|name| name.to_string_lossy())
+        .is_some_and(|name| name.starts_with('.') || name.ends_with('~'))
+}
+
+/// Rebuild whenever one of the `inputs` files or folders changes, until Ctrl-C is pressed.
+///
+/// Events are coalesced so one save triggers one build, build errors are printed
+/// and the watcher keeps going.
+pub fn watch<F>(inputs: &[PathBuf], mut build: F) -> Result<()>
+where
+    F: FnMut() -> Result<String>,
+{
+    let mut files = Vec::new();
+    let mut folders = Vec::new();
+    for input in inputs {
+        let path = input
+            .canonicalize()
+            .map_err(|e| Error::Io(input.clone(), e))?;
+        if path.is_dir() {
+            folders.push(path);
+        } else {
+            files.push(path);
+        }
+    }
+
+    let is_relevant = |event: &Event| {
+        !matches!(event.kind, EventKind::Access(_))
+            && event.paths.iter().any(|path| {
+                !is_temporary(path)
+                    && (files.contains(path) || folders.iter().any(|dir| path.starts_with(dir)))
+            })
+    };
+
+    let (tx, rx) = mpsc::channel();
+    let stop = tx.clone();
+    ctrlc::set_handler(move || {
+        let _ = stop.send(Message::Stop);
+    })
+    .expect("failed to set the Ctrl-C handler");
+
+    let mut watcher = notify::recommended_watcher(move |res| {
+        let _ = tx.send(match res {
+            Ok(event) => Message::Event(event),
+            Err(e) => Message::Error(e),
+        });
+    })?;
+    for folder in &folders {
+        watcher.watch(folder, RecursiveMode::Recursive)?;
+    }
+    // files are watched through their folder, editors that save by replacing
+    // the file would otherwise silently end the watch
+    for file in &files {
+        let dir = file.parent().expect("a file has a parent folder");
+        watcher.watch(dir, RecursiveMode::NonRecursive)?;
+    }
+
+    println!("watching... (press Ctrl-C to stop)");
+
+    let mut builds = 0;
+    'watch: loop {
+        match rx.recv() {
+            Ok(Message::Event(event)) if is_relevant(&event) => {}
+            Ok(Message::Event(_)) => continue,
+            Ok(Message::Error(e)) => {
+                eprintln!("{}", Error::Watch(e));
+                continue;
+            }
+            Ok(Message::Stop) | Err(_) => break 'watch,
+        }
+
+        // drain the rest of the burst before rebuilding
+        loop {
+            match rx.recv_timeout(DEBOUNCE) {
+                Ok(Message::Stop) | Err(RecvTimeoutError::Disconnected) => break 'watch,
+                Ok(_) => continue,
+                Err(RecvTimeoutError::Timeout) => break,
+            }
+        }
+
+        builds += 1;
+        let started = Instant::now();
+        match build() {
+            Ok(summary) => println!(
+                "[build {}] {} in {}ms",
+                builds,
+                summary,
+                started.elapsed().as_millis()
+            ),
+            Err(e) => eprintln!(
+                "[build {}] failed in {}ms\n{}",
+                builds,
+                started.elapsed().as_millis(),
+                e
+            ),
+        }
+    }
+
+    println!("stopped watching");
+    Ok(())
+}
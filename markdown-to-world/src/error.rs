@@ -0,0 +1,27 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// Why the room files could not be turned into a config
+#[derive(Debug)]
+pub enum Error {
+    Io(PathBuf, io::Error),
+    /// A room or translated room file that does not follow the markdown dialect
+    Room(PathBuf, String),
+    /// More than one room is marked `start: true`
+    StartRooms(Vec<String>),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(path, e) => write!(f, "{}: {}", path.to_string_lossy(), e),
+            Error::Room(path, e) => write!(f, "{}: {}", path.to_string_lossy(), e),
+            Error::StartRooms(files) => write!(f, "more than one start room: {}", files.join(", ")),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
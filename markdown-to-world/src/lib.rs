@@ -0,0 +1,1069 @@
+use pray_engine::{Action, Condition, Effect, Line, Npc, Object, Player, Reaction, Response, Room};
+use pray_engine::{Config, Level};
+
+pub use pray_engine::fences::{
+    ActionRefYaml, ActionYaml, ConditionYaml, EffectYaml, ObjectYaml, RoomYaml,
+};
+use pulldown_cmark::{Event, HeadingLevel, Parser, Tag, TagEnd, TextMergeStream};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::vec;
+mod error;
+mod locale;
+mod npc;
+mod types;
+pub use error::{Error, Result};
+use std::path::{Path, PathBuf};
+use types::{IntermediaryAction, IntermediaryNpc, IntermediaryObject, IntermediaryRoom};
+
+static TRACE: AtomicBool = AtomicBool::new(false);
+
+/// Print the markdown events and the state machine transitions while parsing
+pub fn set_trace(enabled: bool) {
+    TRACE.store(enabled, Ordering::Relaxed);
+}
+
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        if TRACE.load(Ordering::Relaxed) {
+            println!($($arg)*);
+        }
+    };
+}
+
+fn get_relative_path(root: &Path, file: &Path) -> Option<PathBuf> {
+    let root = root.canonicalize().ok()?;
+    let file = file.canonicalize().ok()?;
+    file.strip_prefix(root).ok().map(|p| p.to_path_buf())
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum RoomStateMachineStates {
+    None,
+    RoomName,
+    RoomDescription,
+    RoomYAML,
+    Object,
+    // End,
+}
+
+#[derive(Debug)]
+struct RoomStateMachine {
+    pub state: RoomStateMachineStates,
+    pub room: IntermediaryRoom,
+    pub current_object: Option<ObjectStateMachine>,
+}
+
+impl RoomStateMachine {
+    pub fn new(room_id: u64) -> RoomStateMachine {
+        RoomStateMachine {
+            state: RoomStateMachineStates::None,
+            room: IntermediaryRoom {
+                room_id,
+                room_name: "".to_string(),
+                room_description: "".to_string(),
+                room_type: "".to_string(),
+                biome_type: "".to_string(),
+                start: false,
+                flags: Vec::new(),
+                objects: Some(vec![]),
+                npcs: Vec::new(),
+                object_ids: Vec::new(),
+                dir_obj_ids: Vec::new(),
+            },
+            current_object: None,
+        }
+    }
+
+    pub fn after_event(self, event: &Event) -> std::result::Result<Self, String> {
+        let previous_state = self.state;
+        let s = match self.state {
+            RoomStateMachineStates::None => self.none(event),
+            RoomStateMachineStates::RoomName => self.room_name(event),
+            RoomStateMachineStates::RoomDescription => self.room_description(event),
+            RoomStateMachineStates::RoomYAML => self.room_yaml(event),
+            RoomStateMachineStates::Object => self.object(event),
+            // RoomStateMachineStates::End => panic!("Already Reached The End"),
+        }?;
+
+        if previous_state != s.state {
+            trace!("= = = =");
+            trace!("ROOM {:?}", s.state);
+            trace!("= = = =");
+        }
+        Ok(s)
+    }
+
+    fn none(mut self, event: &Event) -> std::result::Result<Self, String> {
+        if let Event::Start(Tag::Heading {
+            level: HeadingLevel::H1,
+            ..
+        }) = event
+        {
+            self.state = RoomStateMachineStates::RoomName
+        }
+        Ok(self)
+    }
+
+    fn room_name(mut self, event: &Event) -> std::result::Result<Self, String> {
+        match event {
+            Event::Text(text) => {
+                self.room.room_name.push_str(text.to_string().as_str());
+            }
+            Event::End(TagEnd::Heading(HeadingLevel::H1)) => {
+                self.state = RoomStateMachineStates::RoomDescription
+            }
+            _ => {}
+        }
+        Ok(self)
+    }
+
+    fn room_description(mut self, event: &Event) -> std::result::Result<Self, String> {
+        match event {
+            Event::Start(tag) => match &tag {
+                Tag::Paragraph => {
+                    if let Some(n) = self.room.room_description.chars().last() {
+                        if n != '\n' {
+                            self.room.room_description.push('\n');
+                        }
+                    }
+                }
+
+                Tag::CodeBlock(_) => self.state = RoomStateMachineStates::RoomYAML,
+                _ => {}
+            },
+            Event::Text(text) => {
+                self.room
+                    .room_description
+                    .push_str(text.to_string().as_str());
+            }
+            _ => {}
+        }
+        Ok(self)
+    }
+
+    fn room_yaml(mut self, event: &Event) -> std::result::Result<Self, String> {
+        if let Event::Text(text) = event {
+            let room_yaml: RoomYaml = serde_yml::from_str(text.as_ref()).map_err(|e| {
+                format!("invalid YAML block of room {}: {}", self.room.room_name, e)
+            })?;
+            self.room.room_type = room_yaml.room_type;
+            self.room.biome_type = room_yaml.biome_type;
+            self.room.start = room_yaml.start.unwrap_or(false);
+            self.room.flags = room_yaml.flags.unwrap_or_default();
+            self.state = RoomStateMachineStates::Object
+        }
+        Ok(self)
+    }
+
+    fn object(mut self, event: &Event) -> std::result::Result<Self, String> {
+        let mut match_event = true;
+        if let Some(object_state_machine) = self.current_object {
+            match_event = false;
+            let new_state = object_state_machine.after_event(event)?;
+            if new_state.state == ObjectStateMachineStates::End {
+                let obj = new_state.into_object();
+                self.current_object = None;
+                if let Some(ref mut vector) = self.room.objects {
+                    vector.push(obj);
+                }
+                match_event = true;
+            } else {
+                self.current_object = Some(new_state);
+            }
+        }
+
+        if match_event {
+            if let Event::Start(Tag::Heading {
+                level: HeadingLevel::H2,
+                ..
+            }) = event
+            {
+                let object_id = calculate_object_id(
+                    self.room.room_id,
+                    self.room.objects.clone().unwrap().len().try_into().unwrap(),
+                );
+                let sm = ObjectStateMachine::new(object_id, HeadingLevel::H2);
+                let state = sm.state;
+                self.current_object = Some(sm);
+                trace!("= = = =");
+                trace!("OBJECT {:?}", state);
+                trace!("= = = =");
+            }
+        }
+
+        Ok(self)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum ObjectStateMachineStates {
+    ObjectDescription,
+    ObjectYAML,
+    ObjectActions,
+    End,
+}
+
+#[derive(Debug)]
+struct ObjectStateMachine {
+    pub state: ObjectStateMachineStates,
+    /// H2 for the objects of a room, H3 for the objects inside them
+    pub level: HeadingLevel,
+    pub object: IntermediaryObject,
+    pub current_action: Option<ActionStateMachine>,
+    pub current_object: Option<Box<ObjectStateMachine>>,
+}
+
+/// `### actions` introduces the actions of an object, any other H3 an object inside it
+fn is_actions_label(heading: &str) -> bool {
+    heading
+        .trim()
+        .trim_end_matches(':')
+        .eq_ignore_ascii_case("actions")
+}
+
+impl ObjectStateMachine {
+    pub fn new(object_id: u64, level: HeadingLevel) -> ObjectStateMachine {
+        ObjectStateMachine {
+            state: ObjectStateMachineStates::ObjectDescription,
+            level,
+            object: IntermediaryObject {
+                obj_id: object_id,
+                actions: Some(vec![]),
+                destination: None,
+                direction: None,
+                material: "".to_string(),
+                obj_description: "".to_string(),
+                ttype: "".to_string(),
+                takeable: false,
+                inventory: false,
+                hidden: false,
+                contains: Vec::new(),
+            },
+            current_action: None,
+            current_object: None,
+        }
+    }
+
+    pub fn after_event(self, event: &Event) -> std::result::Result<Self, String> {
+        let previous_state = self.state;
+        let s = match self.state {
+            ObjectStateMachineStates::ObjectDescription => self.description(event),
+            ObjectStateMachineStates::ObjectYAML => self.yaml(event),
+            ObjectStateMachineStates::ObjectActions => self.actions(event),
+            ObjectStateMachineStates::End => return Err("Already Reached The End".to_string()),
+        }?;
+
+        if previous_state != s.state {
+            trace!("= = = =");
+            trace!("OBJECT {:?}", s.state);
+            trace!("= = = =");
+        }
+        Ok(s)
+    }
+
+    /// The parsed object, with the object inside it still being parsed if any
+    fn into_object(mut self) -> IntermediaryObject {
+        if let Some(nested) = self.current_object {
+            self.object.contains.push(nested.into_object());
+        }
+        self.object
+    }
+
+    fn description(mut self, event: &Event) -> std::result::Result<Self, String> {
+        match event {
+            Event::Start(tag) => match &tag {
+                Tag::Paragraph => {
+                    if let Some(n) = self.object.obj_description.chars().last() {
+                        if n != '\n' {
+                            self.object.obj_description.push('\n');
+                        }
+                    }
+                }
+
+                Tag::CodeBlock(_kind) => self.state = ObjectStateMachineStates::ObjectYAML,
+
+                _ => {}
+            },
+            Event::Text(text) => {
+                self.object
+                    .obj_description
+                    .push_str(text.to_string().as_str());
+            }
+            _ => {}
+        }
+        Ok(self)
+    }
+
+    fn yaml(mut self, event: &Event) -> std::result::Result<Self, String> {
+        if let Event::Text(text) = event {
+            let object_yaml: ObjectYaml = serde_yml::from_str(text.as_ref()).map_err(|e| {
+                format!(
+                    "invalid YAML block of object {}: {}",
+                    self.object.obj_description.trim(),
+                    e
+                )
+            })?;
+            self.object.material = object_yaml.material;
+            self.object.ttype = object_yaml.ttype;
+            self.object.takeable = object_yaml.takeable.unwrap_or(false);
+            self.object.inventory = object_yaml.inventory.unwrap_or(false);
+            self.object.hidden = object_yaml.hidden.unwrap_or(false);
+            if self.object.inventory && self.level != HeadingLevel::H2 {
+                return Err(format!(
+                    "only the objects of a room can start in the inventory: {}",
+                    self.object.obj_description
+                ));
+            }
+            if let Some(direction) = object_yaml.direction {
+                self.object.direction = Some(
+                    match direction.as_str() {
+                        "North" => "N",
+                        "East" => "E",
+                        "South" => "S",
+                        "West" => "W",
+                        "Up" => "U",
+                        "Down" => "D",
+                        _ => return Err(format!("invalid direction: {}", direction)),
+                    }
+                    .to_string(),
+                );
+            }
+            self.state = ObjectStateMachineStates::ObjectActions
+        }
+        Ok(self)
+    }
+
+    fn actions(mut self, event: &Event) -> std::result::Result<Self, String> {
+        let mut match_event = true;
+        if let Some(object_state_machine) = self.current_object {
+            match_event = false;
+            let new_state = object_state_machine.after_event(event)?;
+            if event == &Event::End(TagEnd::Heading(HeadingLevel::H3))
+                && is_actions_label(&new_state.object.obj_description)
+            {
+                // not an object, the actions that follow are ours again
+                self.current_object = None;
+                return Ok(self);
+            }
+            if new_state.state == ObjectStateMachineStates::End {
+                self.object.contains.push(new_state.into_object());
+                self.current_object = None;
+                match_event = true;
+            } else {
+                self.current_object = Some(Box::new(new_state));
+            }
+        }
+        if !match_event {
+            return Ok(self);
+        }
+        if let Some(action_state_machine) = self.current_action {
+            match_event = false;
+            let new_state = action_state_machine.after_event(event)?;
+            if new_state.state == ActionStateMachineStates::End {
+                let action = new_state.action;
+                self.current_action = None;
+                if let Some(ref mut vector) = self.object.actions {
+                    vector.push(action);
+                }
+                match_event = true;
+                if let Some(destination) = new_state.destination {
+                    if let Some(_current_destination) = self.object.destination {
+                        return Err(format!(
+                            "only one destination per object for now: {}",
+                            self.object.obj_description.trim()
+                        ));
+                    } else {
+                        self.object.destination = Some(destination);
+                    }
+                }
+            } else {
+                self.current_action = Some(new_state);
+            }
+        }
+        if match_event {
+            if let Event::Start(Tag::Heading { level, .. }) = event {
+                match *level {
+                    HeadingLevel::H4 => {
+                        let action_id = calculate_action_id(
+                            self.object.obj_id,
+                            self.object
+                                .actions
+                                .clone()
+                                .unwrap()
+                                .len()
+                                .try_into()
+                                .unwrap(),
+                        );
+                        let sm = ActionStateMachine::new(action_id);
+                        let state = sm.state;
+                        self.current_action = Some(sm);
+                        trace!("= = = =");
+                        trace!("ACTION {:?}", state);
+                        trace!("= = = =");
+                    }
+                    HeadingLevel::H3 if self.level == HeadingLevel::H2 => {
+                        let object_id = calculate_contained_object_id(
+                            self.object.obj_id,
+                            self.object.contains.len().try_into().unwrap(),
+                        );
+                        self.current_object = Some(Box::new(ObjectStateMachine::new(
+                            object_id,
+                            HeadingLevel::H3,
+                        )));
+                        trace!("= = = =");
+                        trace!(
+                            "NESTED OBJECT {:?}",
+                            ObjectStateMachineStates::ObjectDescription
+                        );
+                        trace!("= = = =");
+                    }
+                    level if level <= self.level => self.state = ObjectStateMachineStates::End,
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(self)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum ActionStateMachineStates {
+    ActionEffectDescription,
+    ActionYAML,
+    End,
+}
+
+#[derive(Debug)]
+struct ActionStateMachine {
+    pub state: ActionStateMachineStates,
+    pub action: IntermediaryAction,
+    pub destination: Option<String>,
+}
+
+impl ActionStateMachine {
+    pub fn new(action_id: u64) -> ActionStateMachine {
+        ActionStateMachine {
+            state: ActionStateMachineStates::ActionEffectDescription,
+            action: IntermediaryAction {
+                action_id,
+                effects: Vec::new(),
+                requires: Vec::new(),
+                d_bit: true,
+                d_bit_text: "".to_string(),
+                enabled: true,
+                revertable: false,
+                ttype: "".to_string(),
+            },
+            destination: None,
+        }
+    }
+
+    pub fn after_event(self, event: &Event) -> std::result::Result<Self, String> {
+        let previous_state = self.state;
+        let s = match self.state {
+            ActionStateMachineStates::ActionEffectDescription => self.effect_description(event),
+            ActionStateMachineStates::ActionYAML => self.yaml(event),
+            ActionStateMachineStates::End => return Err("Already Reached The End".to_string()),
+        }?;
+
+        if previous_state != s.state {
+            trace!("= = = =");
+            trace!("ACTION: {:?}", s.state);
+            trace!("= = = =");
+        }
+        Ok(s)
+    }
+
+    fn effect_description(mut self, event: &Event) -> std::result::Result<Self, String> {
+        match event {
+            Event::Start(tag) => match &tag {
+                Tag::Paragraph => {
+                    if let Some(n) = self.action.d_bit_text.chars().last() {
+                        if n != '\n' {
+                            self.action.d_bit_text.push('\n');
+                        }
+                    }
+                }
+
+                Tag::Link {
+                    link_type: _,
+                    dest_url,
+                    title,
+                    id: _,
+                } => {
+                    self.action.d_bit_text = title.to_string();
+                    self.destination = Some(dest_url.to_string());
+                    self.action.ttype = "Open".to_string();
+                }
+
+                Tag::CodeBlock(_kind) => self.state = ActionStateMachineStates::ActionYAML,
+
+                _ => {}
+            },
+
+            Event::End(tag) => match &tag {
+                TagEnd::Link => self.state = ActionStateMachineStates::End,
+                TagEnd::CodeBlock => self.state = ActionStateMachineStates::End,
+
+                _ => {}
+            },
+            Event::Text(text) => {
+                self.action.d_bit_text.push_str(text.to_string().as_str());
+            }
+            _ => {}
+        }
+        Ok(self)
+    }
+
+    fn yaml(mut self, event: &Event) -> std::result::Result<Self, String> {
+        if let Event::Text(text) = event {
+            let action_yaml: ActionYaml = serde_yml::from_str(text.as_ref()).map_err(|e| {
+                format!(
+                    "invalid YAML block of action {}: {}",
+                    self.action.d_bit_text.trim(),
+                    e
+                )
+            })?;
+
+            // `affects` predates effects, it enables the action first
+            self.action.effects = action_yaml
+                .affects
+                .map(|action| EffectYaml::Enable { action })
+                .into_iter()
+                .chain(action_yaml.effects.unwrap_or_default())
+                .collect();
+            self.action.requires = action_yaml.requires.unwrap_or_default();
+            if let Some(d_bit) = action_yaml.d_bit {
+                self.action.d_bit = d_bit;
+            } else {
+                self.action.d_bit = true;
+            }
+
+            if let Some(enabled) = action_yaml.enabled {
+                self.action.enabled = enabled;
+            } else {
+                self.action.enabled = true;
+            }
+            if let Some(revertable) = action_yaml.revertable {
+                self.action.revertable = revertable;
+            } else {
+                self.action.revertable = false;
+            }
+
+            self.action.ttype = action_yaml.ttype;
+
+            self.state = ActionStateMachineStates::End
+        }
+        Ok(self)
+    }
+}
+
+/// Objects by their heading, the first line of their description, for effects and conditions
+/// naming objects of any room
+type Headings = HashMap<String, Vec<u64>>;
+
+fn collect_headings(iobjects: &[IntermediaryObject], headings: &mut Headings) {
+    for iobj in iobjects {
+        let heading = iobj.obj_description.lines().next().unwrap_or_default();
+        headings
+            .entry(heading.trim().to_string())
+            .or_default()
+            .push(iobj.obj_id);
+        collect_headings(&iobj.contains, headings);
+    }
+}
+
+/// `objID` of the only object with this heading
+fn resolve_heading(headings: &Headings, heading: &str) -> std::result::Result<u64, String> {
+    match headings.get(heading.trim()).map(Vec::as_slice) {
+        Some([obj_id]) => Ok(*obj_id),
+        Some(_) => Err(format!("more than one object is called `{}`", heading)),
+        None => Err(format!("no object is called `{}`", heading)),
+    }
+}
+
+/// Resolve a reference to an action of the room, by ID or by index among the actions
+/// of the object, `iobj` unless another object of the room is given
+fn resolve_action(
+    iref: &ActionRefYaml,
+    iobj: Option<&IntermediaryObject>,
+    iobjetcs: &[IntermediaryObject],
+) -> std::result::Result<u64, String> {
+    if let Some(id) = iref.action_id {
+        return Ok(id);
+    }
+    let target = if let Some(id) = iref.object_id {
+        iobjetcs
+            .iter()
+            .find(|iobj| iobj.obj_id == id)
+            .ok_or_else(|| format!("no object {} in the room", id))?
+    } else if let Some(index) = iref.object_index {
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| iobjetcs.get(index))
+            .ok_or_else(|| format!("no object at index {} in the room", index))?
+    } else if let Some(iobj) = iobj {
+        iobj
+    } else {
+        return Err("need object_index or object_id".to_string());
+    };
+    let Some(index) = iref.action_index else {
+        return Err("need action_index or action_id".to_string());
+    };
+    usize::try_from(index)
+        .ok()
+        .and_then(|index| target.actions.as_deref().unwrap_or_default().get(index))
+        .map(|iaction| iaction.action_id)
+        .ok_or_else(|| {
+            format!(
+                "no action at index {} in object {}",
+                index,
+                target.obj_description.trim()
+            )
+        })
+}
+
+/// Resolve an effect, rooms are named by their markdown file
+fn resolve_effect(
+    ieffect: &EffectYaml,
+    iobj: Option<&IntermediaryObject>,
+    iobjetcs: &[IntermediaryObject],
+    headings: &Headings,
+) -> std::result::Result<Effect, String> {
+    let room = |file: &String| calculate_room_id(file);
+    let effect = match ieffect {
+        EffectYaml::Enable { action } => Effect::Enable {
+            action: resolve_action(action, iobj, iobjetcs)?,
+        },
+        EffectYaml::Disable { action } => Effect::Disable {
+            action: resolve_action(action, iobj, iobjetcs)?,
+        },
+        EffectYaml::Toggle { action } => Effect::Toggle {
+            action: resolve_action(action, iobj, iobjetcs)?,
+        },
+        EffectYaml::Trigger { action } => Effect::Trigger {
+            action: resolve_action(action, iobj, iobjetcs)?,
+        },
+        EffectYaml::Move { room: file } => Effect::Move { room: room(file) },
+        EffectYaml::Spawn { object, room: file } => Effect::Spawn {
+            object: resolve_heading(headings, object)?,
+            room: file.as_ref().map(room),
+        },
+        EffectYaml::Destroy { object } => Effect::Destroy {
+            object: resolve_heading(headings, object)?,
+        },
+        EffectYaml::Raise { flag, room: file } => Effect::Raise {
+            flag: flag.clone(),
+            room: file.as_ref().map(room),
+        },
+        EffectYaml::Lower { flag, room: file } => Effect::Lower {
+            flag: flag.clone(),
+            room: file.as_ref().map(room),
+        },
+    };
+    Ok(effect)
+}
+
+/// Resolve a condition, `holding` names an object of any room by its heading
+fn resolve_condition(
+    icondition: &ConditionYaml,
+    iobj: &IntermediaryObject,
+    iobjetcs: &[IntermediaryObject],
+    headings: &Headings,
+) -> std::result::Result<Condition, String> {
+    let condition = match icondition {
+        ConditionYaml::Holding { holding } => Condition::Holding {
+            holding: resolve_heading(headings, holding)?,
+        },
+        ConditionYaml::Fired { fired } => Condition::Fired {
+            fired: resolve_action(fired, Some(iobj), iobjetcs)?,
+        },
+        ConditionYaml::Flag { flag } => Condition::Flag { flag: flag.clone() },
+    };
+    Ok(condition)
+}
+
+/// Resolve the effects and conditions of the actions of `iobj`, and of the objects inside it.
+/// Actions are referenced by index among the objects of the room.
+fn build_object(
+    iobj: &IntermediaryObject,
+    iobjetcs: &[IntermediaryObject],
+    headings: &Headings,
+) -> std::result::Result<Object, String> {
+    let mut actions: Vec<Action> = vec![];
+    let iactions = iobj.actions.as_deref().unwrap_or_default();
+    for iaction in iactions {
+        let action = Action {
+            action_id: iaction.action_id,
+            effects: iaction
+                .effects
+                .iter()
+                .map(|ieffect| resolve_effect(ieffect, Some(iobj), iobjetcs, headings))
+                .collect::<std::result::Result<_, _>>()?,
+            requires: iaction
+                .requires
+                .iter()
+                .map(|icondition| resolve_condition(icondition, iobj, iobjetcs, headings))
+                .collect::<std::result::Result<_, _>>()?,
+            d_bit: iaction.d_bit,
+            d_bit_text: iaction.d_bit_text.clone(),
+            enabled: iaction.enabled,
+            revertable: iaction.revertable,
+            ttype: iaction.ttype.clone(),
+        };
+        actions.push(action);
+    }
+
+    Ok(Object {
+        actions: Some(actions),
+        destination: iobj.destination.clone(),
+        direction: iobj.direction.clone(),
+        material: iobj.material.clone(),
+        obj_description: iobj.obj_description.clone(),
+        obj_id: iobj.obj_id,
+        ttype: iobj.ttype.clone(),
+        takeable: iobj.takeable,
+        hidden: iobj.hidden,
+        contains: iobj
+            .contains
+            .iter()
+            .map(|contained| build_object(contained, iobjetcs, headings))
+            .collect::<std::result::Result<_, _>>()?,
+    })
+}
+
+/// Parse a room file, `room_file` being its path relative to the rooms folder
+fn parse_room(file_path: &Path, room_file: &str) -> Result<IntermediaryRoom> {
+    let file_content =
+        fs::read_to_string(file_path).map_err(|e| Error::Io(file_path.to_path_buf(), e))?;
+    let room_error = |e: String| Error::Room(file_path.to_path_buf(), e);
+    let (file_content, sections) = npc::split(&file_content);
+
+    let room_id = calculate_room_id(&room_file);
+    let mut state_machine = RoomStateMachine::new(room_id);
+    trace!("= = = =");
+    trace!("ROOM {:?}", state_machine.state.clone());
+    trace!("= = = =");
+
+    let iterator = TextMergeStream::new(Parser::new(file_content.as_str()));
+
+    for event in iterator {
+        match &event {
+            Event::Start(tag) => {
+                trace!("<{:?}>", tag);
+            }
+            Event::End(tag) => {
+                trace!("</{:?}>", tag);
+            }
+            Event::Text(text) => {
+                trace!("{}", text);
+            }
+            _ => {}
+        }
+        state_machine = state_machine.after_event(&event).map_err(room_error)?;
+    }
+
+    if let Some(obj_sm) = state_machine.current_object {
+        if let Some(ref mut vector) = state_machine.room.objects {
+            vector.push(obj_sm.into_object());
+        }
+    }
+
+    if let Some(objects) = &mut state_machine.room.objects {
+        // inventory and hidden objects are not in the room when the level starts
+        for object in objects
+            .iter()
+            .filter(|object| !object.inventory && !object.hidden)
+        {
+            if let Some(_destination) = &object.destination {
+                state_machine.room.dir_obj_ids.push(object.obj_id);
+            } else {
+                state_machine.room.object_ids.push(object.obj_id);
+            }
+        }
+    }
+
+    for (index, section) in sections.iter().enumerate() {
+        let npc_id = calculate_npc_id(room_id, index as u64);
+        let inpc = npc::parse(section, npc_id, |index| calculate_line_id(npc_id, index))
+            .map_err(room_error)?;
+        trace!("NPC {:?}", inpc);
+        state_machine.room.npcs.push(inpc);
+    }
+
+    trace!("====================================");
+    Ok(state_machine.room)
+}
+
+/// Resolve the patrol, the answers and the reaction effects of a character.
+/// Actions of reaction effects are referenced by index among the objects of the room.
+fn build_npc(
+    inpc: &IntermediaryNpc,
+    iobjetcs: &[IntermediaryObject],
+    headings: &Headings,
+) -> std::result::Result<Npc, String> {
+    let line_id = |slug: &String| {
+        let line = inpc.dialogue.iter().find(|line| line.slug == *slug);
+        match line {
+            Some(line) => Ok(line.line_id),
+            None => Err(format!(
+                "no line `{}` in the dialogue of {}",
+                slug, inpc.name
+            )),
+        }
+    };
+    let mut dialogue = Vec::new();
+    for iline in &inpc.dialogue {
+        let mut responses = Vec::new();
+        for iresponse in &iline.responses {
+            responses.push(Response {
+                text: iresponse.text.clone(),
+                next: iresponse.next.as_ref().map(line_id).transpose()?,
+            });
+        }
+        dialogue.push(Line {
+            line_id: iline.line_id,
+            text: iline.text.clone(),
+            responses,
+        });
+    }
+    let mut reactions: BTreeMap<String, Reaction> = BTreeMap::new();
+    for ireaction in &inpc.reactions {
+        let reaction = Reaction {
+            text: ireaction.text.clone(),
+            effects: ireaction
+                .effects
+                .iter()
+                .map(|ieffect| resolve_effect(ieffect, None, iobjetcs, headings))
+                .collect::<std::result::Result<_, _>>()?,
+        };
+        reactions.insert(ireaction.ttype.clone(), reaction);
+    }
+    Ok(Npc {
+        npc_id: inpc.npc_id,
+        name: inpc.name.clone(),
+        description: inpc.description.clone(),
+        dialogue,
+        reactions,
+        patrol: inpc.patrol.iter().map(calculate_room_id).collect(),
+    })
+}
+
+/// Parse every room file in `dir_path` into a single level config
+pub fn build_config(dir_path: &str) -> Result<Config> {
+    let mut rooms: Vec<Room> = Vec::new();
+    let mut start_rooms: Vec<(u64, String)> = Vec::new();
+    let mut inventory: Vec<Object> = Vec::new();
+
+    // read_dir order is platform dependent, sort so the generated config is stable
+    let io_error = |e| Error::Io(PathBuf::from(dir_path), e);
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir_path)
+        .map_err(io_error)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<_>>()
+        .map_err(io_error)?;
+    paths.sort();
+
+    // every room is parsed first, conditions can name the objects of other rooms
+    let mut irooms: Vec<(IntermediaryRoom, String, PathBuf)> = Vec::new();
+    // translated room files are read once their room is parsed
+    let mut translations: Vec<(PathBuf, String, String)> = Vec::new();
+    for file_path in paths {
+        if file_path.is_file() {
+            let roon_id_string = get_relative_path(Path::new(dir_path), &file_path)
+                .ok_or_else(|| {
+                    let e = "not in the rooms folder".to_string();
+                    Error::Room(file_path.clone(), e)
+                })?
+                .to_string_lossy()
+                .into_owned();
+            match locale::translation_of(&roon_id_string) {
+                Ok(Some((room_file, locale))) => {
+                    translations.push((file_path, room_file, locale));
+                    continue;
+                }
+                Ok(None) => {}
+                Err(code) => eprintln!(
+                    "warning: {}: `{}` is not a locale code, the file is read as a room",
+                    file_path.to_string_lossy(),
+                    code
+                ),
+            }
+            trace!("{:?} => {:?}", file_path, roon_id_string);
+            let iroom = parse_room(&file_path, &roon_id_string)?;
+            irooms.push((iroom, roon_id_string, file_path));
+        }
+    }
+    let mut locales: BTreeMap<String, BTreeMap<String, String>> = BTreeMap::new();
+    for (file_path, room_file, locale) in translations {
+        let Some((iroom, _, _)) = irooms.iter().find(|(_, file, _)| *file == room_file) else {
+            let e = format!("translates {} which does not exist", room_file);
+            return Err(Error::Room(file_path, e));
+        };
+        let content =
+            fs::read_to_string(&file_path).map_err(|e| Error::Io(file_path.clone(), e))?;
+        let texts =
+            locale::translate(iroom, &content).map_err(|e| Error::Room(file_path.clone(), e))?;
+        locales.entry(locale).or_default().extend(texts);
+    }
+    let mut headings = Headings::new();
+    for (iroom, _, _) in &irooms {
+        collect_headings(iroom.objects.as_deref().unwrap_or_default(), &mut headings);
+    }
+
+    for (iroom, roon_id_string, file_path) in irooms {
+        let room_error = |e: String| Error::Room(file_path.clone(), e);
+        if iroom.start {
+            start_rooms.push((iroom.room_id, roon_id_string));
+        }
+        let iobjetcs = iroom.objects.as_deref().unwrap_or_default();
+        let npcs = iroom
+            .npcs
+            .iter()
+            .map(|inpc| build_npc(inpc, iobjetcs, &headings))
+            .collect::<std::result::Result<_, _>>()
+            .map_err(room_error)?;
+        let mut room = Room {
+            room_id: iroom.room_id,
+            room_name: iroom.room_name,
+            room_description: iroom.room_description,
+            room_type: iroom.room_type,
+            biome_type: iroom.biome_type,
+            objects: Some(vec![]),
+            object_ids: iroom.object_ids,
+            dir_obj_ids: iroom.dir_obj_ids,
+            flags: iroom.flags,
+            npcs,
+        };
+
+        if let Some(iobjetcs) = iroom.objects {
+            let mut objects: Vec<Object> = vec![];
+            for iobj in &iobjetcs {
+                let object = build_object(iobj, &iobjetcs, &headings).map_err(room_error)?;
+                if iobj.inventory {
+                    inventory.push(object);
+                } else {
+                    objects.push(object);
+                }
+            }
+            room.objects = Some(objects);
+        }
+
+        rooms.push(room);
+    }
+
+    if start_rooms.len() > 1 {
+        let files: Vec<String> = start_rooms.into_iter().map(|(_, file)| file).collect();
+        return Err(Error::StartRooms(files));
+    }
+    let start_room = start_rooms.pop().map(|(room_id, _)| room_id);
+    let player = if start_room.is_some() || !inventory.is_empty() {
+        Some(Player {
+            start_room,
+            inventory,
+            ..Player::default()
+        })
+    } else {
+        None
+    };
+
+    Ok(Config {
+        version: pray_engine::migrate::VERSION,
+        include: Vec::new(),
+        locales,
+        levels: vec![Level {
+            level_name: "test".to_string(),
+            player,
+            rooms,
+        }],
+    })
+}
+
+fn calculate_room_id<T: Hash>(t: &T) -> u64 {
+    let mut s = DefaultHasher::new();
+    t.hash(&mut s);
+    s.finish()
+}
+
+fn calculate_object_id(room_id: u64, index: u64) -> u64 {
+    let mut s = DefaultHasher::new();
+    room_id.hash(&mut s);
+    index.hash(&mut s);
+    s.finish()
+}
+
+/// Differs from the ID of the container's action at the same index
+fn calculate_contained_object_id(container_id: u64, index: u64) -> u64 {
+    let mut s = DefaultHasher::new();
+    "contains".hash(&mut s);
+    container_id.hash(&mut s);
+    index.hash(&mut s);
+    s.finish()
+}
+
+fn calculate_npc_id(room_id: u64, index: u64) -> u64 {
+    let mut s = DefaultHasher::new();
+    "npc".hash(&mut s);
+    room_id.hash(&mut s);
+    index.hash(&mut s);
+    s.finish()
+}
+
+fn calculate_line_id(npc_id: u64, index: u64) -> u64 {
+    let mut s = DefaultHasher::new();
+    npc_id.hash(&mut s);
+    index.hash(&mut s);
+    s.finish()
+}
+
+fn calculate_action_id(object_id: u64, index: u64) -> u64 {
+    let mut s = DefaultHasher::new();
+    object_id.hash(&mut s);
+    index.hash(&mut s);
+    s.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_broken_room_files() {
+        let dir = std::env::temp_dir().join(format!("pray-rooms-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let room = |yaml: &str| {
+            format!(
+                "# Barn\n\na barn\n\n```yaml\n{}\n```\n\n## a bale of hay\n\n```yaml\ntype: Hay\nmaterial: Straw\n```\n",
+                yaml
+            )
+        };
+        fs::write(
+            dir.join("barn.md"),
+            room("roomType: Barn\nbiomeType: Prairie"),
+        )
+        .unwrap();
+        let config = build_config(dir.to_str().unwrap()).unwrap();
+        assert_eq!(config.levels[0].rooms[0].room_type, "Barn");
+
+        fs::write(
+            dir.join("barn.md"),
+            room("roomType: [Barn\nbiomeType: Prairie"),
+        )
+        .unwrap();
+        let Err(Error::Room(path, message)) = build_config(dir.to_str().unwrap()) else {
+            panic!("expected the room file to be rejected");
+        };
+        assert_eq!(path, dir.join("barn.md"));
+        assert!(
+            message.starts_with("invalid YAML block of room Barn"),
+            "{}",
+            message
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,309 @@
+//! Translated room files: `elis-barn.fr.md` next to `elis-barn.md` holds the French texts of the room.
+//! It has the headings of the room file in the same order, without the YAML blocks
+//! except the `npc: true` of characters, and texts are matched with the room by position:
+//!
+//! ```markdown
+//! # Eli's Barn
+//!
+//! une grange qui sent l'essence
+//!
+//! ## une botte de foin
+//!
+//! ### actions
+//!
+//! #### la botte s'embrase
+//! ```
+//!
+//! The H1 is the name of the room, it is not translated.
+
+use crate::npc;
+use crate::types::{IntermediaryObject, IntermediaryRoom};
+use pray_engine::locale::TextId;
+use pulldown_cmark::{Event, HeadingLevel, Parser, Tag, TagEnd, TextMergeStream};
+use std::collections::BTreeMap;
+
+/// Whether `code` is a locale code: a language, then optionally a script and a region,
+/// e.g. `fr`, `pt-BR`, `zh-Hant` or `es-419`
+pub fn is_locale(code: &str) -> bool {
+    let all = |subtag: &str, f: fn(&char) -> bool| subtag.chars().all(|c| f(&c));
+    let language =
+        |subtag: &&str| (2..=3).contains(&subtag.len()) && all(subtag, char::is_ascii_lowercase);
+    let script = |subtag: &&str| {
+        subtag.len() == 4
+            && subtag.starts_with(|c: char| c.is_ascii_uppercase())
+            && all(&subtag[1..], char::is_ascii_lowercase)
+    };
+    let region = |subtag: &&str| {
+        (subtag.len() == 2 && all(subtag, char::is_ascii_uppercase))
+            || (subtag.len() == 3 && all(subtag, char::is_ascii_digit))
+    };
+    let mut subtags = code.split('-').peekable();
+    if subtags.next_if(language).is_none() {
+        return false;
+    }
+    subtags.next_if(script);
+    subtags.next_if(region);
+    subtags.next().is_none()
+}
+
+/// `elis-barn.fr.md` is the `fr` translation of `elis-barn.md`, returns the room file and the locale.
+/// A file name with a part before its extension that is not a locale code, e.g. `barn.draft.md`,
+/// is an error with that part.
+pub fn translation_of(file_name: &str) -> Result<Option<(String, String)>, String> {
+    let Some((stem, extension)) = file_name.rsplit_once('.') else {
+        return Ok(None);
+    };
+    let Some((room, locale)) = stem.rsplit_once('.') else {
+        return Ok(None);
+    };
+    if !is_locale(locale) {
+        return Err(locale.to_string());
+    }
+    Ok(Some((
+        format!("{}.{}", room, extension),
+        locale.to_string(),
+    )))
+}
+
+#[derive(Default, Debug)]
+struct ObjectTexts {
+    description: String,
+    actions: Vec<String>,
+    contains: Vec<ObjectTexts>,
+}
+
+#[derive(Default, Debug)]
+struct RoomTexts {
+    description: String,
+    objects: Vec<ObjectTexts>,
+    /// The text goes to an object inside the last object
+    contained: bool,
+    /// The text goes to the last action
+    action: bool,
+}
+
+impl RoomTexts {
+    /// The object headings and paragraphs go to
+    fn object(&mut self) -> Option<&mut ObjectTexts> {
+        let object = self.objects.last_mut()?;
+        if self.contained {
+            object.contains.last_mut()
+        } else {
+            Some(object)
+        }
+    }
+
+    /// The text paragraphs go to
+    fn text(&mut self) -> Option<&mut String> {
+        if self.objects.is_empty() {
+            return Some(&mut self.description);
+        }
+        let action = self.action;
+        let object = self.object()?;
+        if action {
+            object.actions.last_mut()
+        } else {
+            Some(&mut object.description)
+        }
+    }
+
+    fn heading(&mut self, level: HeadingLevel, text: String) -> Result<(), String> {
+        match level {
+            HeadingLevel::H2 => {
+                self.objects.push(ObjectTexts {
+                    description: text,
+                    ..ObjectTexts::default()
+                });
+                self.contained = false;
+            }
+            HeadingLevel::H3 if crate::is_actions_label(&text) => self.contained = false,
+            HeadingLevel::H3 => {
+                let Some(object) = self.objects.last_mut() else {
+                    return Err(format!("object `{}` outside of an object", text));
+                };
+                object.contains.push(ObjectTexts {
+                    description: text,
+                    ..ObjectTexts::default()
+                });
+                self.contained = true;
+            }
+            HeadingLevel::H4 => {
+                let Some(object) = self.object() else {
+                    return Err(format!("action `{}` outside of an object", text));
+                };
+                object.actions.push(text);
+                self.action = true;
+                return Ok(());
+            }
+            _ => {}
+        }
+        self.action = false;
+        Ok(())
+    }
+}
+
+/// The texts of the room part of a translated file, paragraphs joined like the room file
+fn parse(markdown: &str) -> Result<RoomTexts, String> {
+    let mut texts = RoomTexts::default();
+    let mut heading: Option<String> = None;
+    let mut in_code = false;
+    for event in TextMergeStream::new(Parser::new(markdown)) {
+        match event {
+            Event::Start(Tag::Heading { .. }) => heading = Some(String::new()),
+            Event::End(TagEnd::Heading(level)) => {
+                let text = heading.take().unwrap_or_default();
+                texts.heading(level, text)?;
+            }
+            Event::Start(Tag::CodeBlock(_)) => in_code = true,
+            Event::End(TagEnd::CodeBlock) => in_code = false,
+            Event::Start(Tag::Paragraph) => {
+                if let Some(text) = texts.text() {
+                    if !text.is_empty() && !text.ends_with('\n') {
+                        text.push('\n');
+                    }
+                }
+            }
+            Event::Text(_) if in_code => {}
+            Event::Text(text) => match &mut heading {
+                Some(heading) => heading.push_str(&text),
+                None => {
+                    if let Some(target) = texts.text() {
+                        target.push_str(&text);
+                    }
+                }
+            },
+            _ => {}
+        }
+    }
+    Ok(texts)
+}
+
+/// Pair what the room has with what the translation has, they must be as many
+fn zip<'a, A, B>(
+    what: &str,
+    ours: &'a [A],
+    theirs: &'a [B],
+) -> Result<Vec<(&'a A, &'a B)>, String> {
+    if ours.len() != theirs.len() {
+        return Err(format!(
+            "the translation has {} {}, the room has {}",
+            theirs.len(),
+            what,
+            ours.len()
+        ));
+    }
+    Ok(ours.iter().zip(theirs).collect())
+}
+
+fn translate_objects(
+    iobjects: &[IntermediaryObject],
+    objects: &[ObjectTexts],
+    texts: &mut BTreeMap<String, String>,
+) -> Result<(), String> {
+    for (iobj, object) in zip("objects", iobjects, objects)? {
+        texts.insert(
+            TextId::Object(iobj.obj_id).to_string(),
+            object.description.clone(),
+        );
+        let iactions = iobj.actions.as_deref().unwrap_or_default();
+        for (iaction, text) in zip("actions", iactions, &object.actions)? {
+            texts.insert(TextId::Action(iaction.action_id).to_string(), text.clone());
+        }
+        translate_objects(&iobj.contains, &object.contains, texts)?;
+    }
+    Ok(())
+}
+
+/// The texts of a translated file by text ID, `iroom` being the parsed room file
+pub fn translate(
+    iroom: &IntermediaryRoom,
+    markdown: &str,
+) -> Result<BTreeMap<String, String>, String> {
+    let (markdown, sections) = npc::split(markdown);
+    let room = parse(&markdown)?;
+
+    let mut texts = BTreeMap::new();
+    texts.insert(
+        TextId::Room(iroom.room_id).to_string(),
+        room.description.clone(),
+    );
+    let iobjects = iroom.objects.as_deref().unwrap_or_default();
+    translate_objects(iobjects, &room.objects, &mut texts)?;
+
+    let npcs = sections
+        .iter()
+        .map(|section| npc::parse(section, 0, |_| 0))
+        .collect::<Result<Vec<_>, _>>()?;
+    for (inpc, npc) in zip("characters", &iroom.npcs, &npcs)? {
+        texts.insert(
+            TextId::Npc(inpc.npc_id).to_string(),
+            npc.description.clone(),
+        );
+        for (iline, line) in zip("lines", &inpc.dialogue, &npc.dialogue)? {
+            texts.insert(TextId::Line(iline.line_id).to_string(), line.text.clone());
+            let responses = zip("answers", &iline.responses, &line.responses)?;
+            for (index, (_, response)) in responses.into_iter().enumerate() {
+                let id = TextId::Response(iline.line_id, index);
+                texts.insert(id.to_string(), response.text.clone());
+            }
+        }
+        for reaction in &npc.reactions {
+            if !inpc.reactions.iter().any(|r| r.ttype == reaction.ttype) {
+                return Err(format!(
+                    "{} does not react to {}",
+                    inpc.name, reaction.ttype
+                ));
+            }
+            let id = TextId::Reaction(inpc.npc_id, reaction.ttype.clone());
+            texts.insert(id.to_string(), reaction.text.clone());
+        }
+    }
+
+    // nothing was translated
+    texts.retain(|_, text| !text.is_empty());
+    Ok(texts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn knows_locale_codes() {
+        for code in ["fr", "ast", "pt-BR", "zh-Hant", "zh-Hant-TW", "es-419"] {
+            assert!(is_locale(code), "{}", code);
+        }
+        for code in [
+            "",
+            "draft",
+            "v2",
+            "FR",
+            "f",
+            "french",
+            "pt-br",
+            "pt-BR-Latn",
+            "fr-",
+        ] {
+            assert!(!is_locale(code), "{}", code);
+        }
+    }
+
+    #[test]
+    fn finds_translated_room_files() {
+        assert_eq!(
+            translation_of("elis-barn.fr.md"),
+            Ok(Some(("elis-barn.md".to_string(), "fr".to_string())))
+        );
+        assert_eq!(
+            translation_of("farm/elis-barn.pt-BR.md"),
+            Ok(Some(("farm/elis-barn.md".to_string(), "pt-BR".to_string())))
+        );
+        assert_eq!(translation_of("elis-barn.md"), Ok(None));
+        assert_eq!(translation_of("README"), Ok(None));
+        assert_eq!(
+            translation_of("elis-barn.draft.md"),
+            Err("draft".to_string())
+        );
+        assert_eq!(translation_of("v1.2.md"), Err("2".to_string()));
+    }
+}
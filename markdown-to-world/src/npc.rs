@@ -0,0 +1,376 @@
+//! Characters in the room markdown, an H2 section with `npc: true` in its YAML block:
+//!
+//! ```markdown
+//! ## Eli
+//!
+//! a farmer chewing on a straw
+//!
+//! (YAML block: npc: true, patrol: [elis-barn.md, bensons-plain.md])
+//!
+//! ### dialogue
+//!
+//! #### howdy stranger, you lost?
+//!
+//! - [where am I?](#bensons-plain)
+//! - nope
+//!
+//! #### bensons plain
+//!
+//! ### reactions
+//!
+//! #### Fight
+//!
+//! Eli swings a pitchfork at you
+//!
+//! (YAML block: effects)
+//! ```
+//!
+//! Each H4 of the dialogue is a line, the list below it the answers of the player,
+//! linking to the line the character answers with by the slug of its heading.
+//! Each H4 of the reactions is an action type.
+
+use crate::types::{IntermediaryLine, IntermediaryNpc, IntermediaryReaction, IntermediaryResponse};
+use pray_engine::fences::{NpcYaml, ReactionYaml};
+use pulldown_cmark::{Event, HeadingLevel, Parser, Tag, TagEnd, TextMergeStream};
+
+/// Whether the first YAML block of an H2 section marks a character
+fn is_npc(section: &str) -> bool {
+    let mut lines = section.lines().skip_while(|line| !is_fence(line)).skip(1);
+    let yaml: Vec<&str> = lines.by_ref().take_while(|line| !is_fence(line)).collect();
+    let Ok(value) = serde_yml::from_str::<serde_yml::Value>(&yaml.join("\n")) else {
+        return false;
+    };
+    value.get("npc").and_then(serde_yml::Value::as_bool) == Some(true)
+}
+
+fn is_fence(line: &str) -> bool {
+    let line = line.trim_start();
+    line.starts_with("```") || line.starts_with("~~~")
+}
+
+/// Take the character sections out of a room file, the rest is parsed as the room and its objects
+pub fn split(markdown: &str) -> (String, Vec<String>) {
+    // the room, then one section per H2
+    let mut sections: Vec<String> = vec![String::new()];
+    let mut in_fence = false;
+    for line in markdown.lines() {
+        if is_fence(line) {
+            in_fence = !in_fence;
+        }
+        if !in_fence && (line.starts_with("## ") || line.starts_with("# ")) {
+            sections.push(String::new());
+        }
+        let section = sections
+            .last_mut()
+            .expect("the room section is always there");
+        section.push_str(line);
+        section.push('\n');
+    }
+
+    let mut room = String::new();
+    let mut npcs = Vec::new();
+    for section in sections {
+        if section.starts_with("## ") && is_npc(&section) {
+            npcs.push(section);
+        } else {
+            room.push_str(&section);
+        }
+    }
+    (room, npcs)
+}
+
+/// Lowercase words joined by dashes, how dialogue lines are linked to
+pub fn slug(text: &str) -> String {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Part {
+    Description,
+    Dialogue,
+    Reactions,
+}
+
+fn append(text: &mut String, more: &str) {
+    if !text.is_empty() && !text.ends_with('\n') {
+        text.push('\n');
+    }
+    text.push_str(more);
+}
+
+/// Parse a character section, `line_id` gives the ID of a line by its index
+pub fn parse(
+    section: &str,
+    npc_id: u64,
+    line_id: impl Fn(u64) -> u64,
+) -> Result<IntermediaryNpc, String> {
+    let mut npc = IntermediaryNpc {
+        npc_id,
+        name: String::new(),
+        description: String::new(),
+        patrol: Vec::new(),
+        dialogue: Vec::new(),
+        reactions: Vec::new(),
+    };
+
+    let mut part = Part::Description;
+    let mut heading: Option<String> = None;
+    let mut in_code = false;
+    let mut in_item = false;
+    let mut paragraph = String::new();
+
+    for event in TextMergeStream::new(Parser::new(section)) {
+        match event {
+            Event::Start(Tag::Heading { .. }) => heading = Some(String::new()),
+            Event::End(TagEnd::Heading(level)) => {
+                let text = heading.take().unwrap_or_default().trim().to_string();
+                match level {
+                    HeadingLevel::H2 => npc.name = text,
+                    HeadingLevel::H3 => {
+                        part = match text.to_lowercase().trim_end_matches(':') {
+                            "dialogue" => Part::Dialogue,
+                            "reactions" => Part::Reactions,
+                            _ => {
+                                return Err(format!(
+                                    "unknown part `{}` of character {}",
+                                    text, npc.name
+                                ))
+                            }
+                        }
+                    }
+                    HeadingLevel::H4 if part == Part::Dialogue => {
+                        let index = npc.dialogue.len() as u64;
+                        npc.dialogue.push(IntermediaryLine {
+                            line_id: line_id(index),
+                            slug: slug(&text),
+                            text,
+                            responses: Vec::new(),
+                        })
+                    }
+                    HeadingLevel::H4 if part == Part::Reactions => {
+                        npc.reactions.push(IntermediaryReaction {
+                            ttype: text,
+                            text: String::new(),
+                            effects: Vec::new(),
+                        })
+                    }
+                    _ => {
+                        return Err(format!(
+                            "unexpected heading `{}` in character {}",
+                            text, npc.name
+                        ))
+                    }
+                }
+            }
+            Event::Start(Tag::Item) => {
+                in_item = true;
+                let Some(line) = npc.dialogue.last_mut() else {
+                    return Err(format!(
+                        "answers outside of a dialogue line in character {}",
+                        npc.name
+                    ));
+                };
+                line.responses.push(IntermediaryResponse {
+                    text: String::new(),
+                    next: None,
+                });
+            }
+            Event::End(TagEnd::Item) => in_item = false,
+            Event::Start(Tag::Link { dest_url, .. }) if in_item => {
+                let response = npc.dialogue.last_mut().and_then(|l| l.responses.last_mut());
+                if let Some(response) = response {
+                    response.next = Some(dest_url.trim_start_matches('#').to_string());
+                }
+            }
+            Event::Start(Tag::CodeBlock(_)) => in_code = true,
+            Event::End(TagEnd::CodeBlock) => in_code = false,
+            Event::End(TagEnd::Paragraph) if !in_item => {
+                let text = std::mem::take(&mut paragraph);
+                match part {
+                    Part::Description => append(&mut npc.description, &text),
+                    Part::Dialogue => {
+                        if let Some(line) = npc.dialogue.last_mut() {
+                            append(&mut line.text, &text);
+                        }
+                    }
+                    Part::Reactions => {
+                        if let Some(reaction) = npc.reactions.last_mut() {
+                            append(&mut reaction.text, &text);
+                        }
+                    }
+                }
+            }
+            Event::Text(text) if in_code => match part {
+                Part::Description => {
+                    let npc_yaml: NpcYaml = serde_yml::from_str(text.as_ref()).map_err(|e| {
+                        format!("invalid YAML block of character {}: {}", npc.name, e)
+                    })?;
+                    npc.patrol = npc_yaml.patrol.unwrap_or_default();
+                }
+                Part::Reactions => {
+                    let reaction_yaml: ReactionYaml =
+                        serde_yml::from_str(text.as_ref()).map_err(|e| {
+                            format!("invalid YAML block of a reaction of {}: {}", npc.name, e)
+                        })?;
+                    if let Some(reaction) = npc.reactions.last_mut() {
+                        reaction.effects = reaction_yaml.effects.unwrap_or_default();
+                    }
+                }
+                Part::Dialogue => {
+                    return Err(format!(
+                        "unexpected YAML block in the dialogue of {}",
+                        npc.name
+                    ))
+                }
+            },
+            Event::Text(text) => {
+                if let Some(heading) = &mut heading {
+                    heading.push_str(&text);
+                } else if in_item {
+                    let response = npc.dialogue.last_mut().and_then(|l| l.responses.last_mut());
+                    if let Some(response) = response {
+                        response.text.push_str(&text);
+                    }
+                } else {
+                    paragraph.push_str(&text);
+                }
+            }
+            Event::SoftBreak if !in_item && heading.is_none() => paragraph.push('\n'),
+            _ => {}
+        }
+    }
+    Ok(npc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    const ELI: &str = "## Eli
+
+a farmer chewing on a straw
+
+```yaml
+npc: true
+patrol: [elis-barn.md, bensons-plain.md]
+```
+
+### dialogue
+
+#### howdy stranger, you lost?
+
+- [where am I?](#bensons-plain)
+- nope
+
+#### bensons plain
+
+### reactions
+
+#### Fight
+
+Eli swings a pitchfork at you
+";
+
+    /// A room with a bale of hay and Eli
+    fn room(npc: &str) -> String {
+        format!(
+            "# Eli's Barn\n\na barn\n\n```yaml\nroomType: Barn\nbiomeType: Prairie\n```\n\n\
+             ## a bale of hay\n\n```yaml\ntype: Hay\nmaterial: Straw\n```\n\n{}",
+            npc
+        )
+    }
+
+    #[test]
+    fn splits_characters_from_the_room() {
+        let (room, npcs) = split(&room(ELI));
+        assert!(room.contains("## a bale of hay"));
+        assert!(!room.contains("Eli swings"));
+        assert_eq!(npcs, [ELI]);
+        // a `##` inside a code block is not a section
+        let (room, npcs) = split("# Barn\n\n```\n## Eli\nnpc: true\n```\n");
+        assert!(npcs.is_empty());
+        assert!(room.contains("## Eli"));
+    }
+
+    #[test]
+    fn only_sections_with_npc_true_are_characters() {
+        assert!(is_npc(ELI));
+        assert!(!is_npc("## a bale of hay\n\n```yaml\ntype: Hay\n```\n"));
+        assert!(!is_npc("## Eli\n\n```yaml\nnpc: false\n```\n"));
+        assert!(!is_npc("## Eli\n\nno YAML block\n"));
+    }
+
+    #[test]
+    fn parses_dialogue_lines_and_patrol() {
+        let npc = parse(ELI, 7, |index| 100 + index).unwrap();
+        assert_eq!(npc.name, "Eli");
+        assert_eq!(npc.description, "a farmer chewing on a straw");
+        assert_eq!(npc.patrol, ["elis-barn.md", "bensons-plain.md"]);
+
+        let lines: Vec<(u64, &str, &str)> = npc
+            .dialogue
+            .iter()
+            .map(|line| (line.line_id, line.slug.as_str(), line.text.as_str()))
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                (100, "howdy-stranger-you-lost", "howdy stranger, you lost?"),
+                (101, "bensons-plain", "bensons plain"),
+            ]
+        );
+        assert_eq!(
+            npc.dialogue[0].responses,
+            [
+                IntermediaryResponse {
+                    text: "where am I?".to_string(),
+                    next: Some("bensons-plain".to_string()),
+                },
+                IntermediaryResponse {
+                    text: "nope".to_string(),
+                    next: None,
+                },
+            ]
+        );
+        assert_eq!(npc.reactions[0].ttype, "Fight");
+        assert_eq!(npc.reactions[0].text, "Eli swings a pitchfork at you");
+    }
+
+    #[test]
+    fn rejects_misplaced_parts() {
+        let error = parse("## Eli\n\n### chores\n", 7, |index| index).unwrap_err();
+        assert_eq!(error, "unknown part `chores` of character Eli");
+        let error = parse("## Eli\n\n- hello\n", 7, |index| index).unwrap_err();
+        assert_eq!(error, "answers outside of a dialogue line in character Eli");
+    }
+
+    #[test]
+    fn links_answers_to_lines_by_slug() {
+        let dir = std::env::temp_dir().join(format!("pray-npc-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("elis-barn.md"), room(ELI)).unwrap();
+        let config = crate::build_config(dir.to_str().unwrap()).unwrap();
+        let barn = &config.levels[0].rooms[0];
+        let eli = &barn.npcs[0];
+        assert_eq!(
+            eli.dialogue[0].responses[0].next,
+            Some(eli.dialogue[1].line_id)
+        );
+        assert_eq!(eli.patrol[0], barn.room_id);
+
+        // the link does not match the slug of any line
+        let broken = ELI.replace("(#bensons-plain)", "(#benson-s-plain)");
+        fs::write(dir.join("elis-barn.md"), room(&broken)).unwrap();
+        let Err(crate::Error::Room(_, message)) = crate::build_config(dir.to_str().unwrap()) else {
+            panic!("expected the dialogue link to be rejected");
+        };
+        assert_eq!(message, "no line `benson-s-plain` in the dialogue of Eli");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,17 +1,6 @@
+use pray_engine::fences::{ConditionYaml, EffectYaml};
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
-#[serde(rename_all = "camelCase")]
-pub struct IntermediaryEffect {
-    pub room: Option<String>,
-    #[serde(rename = "objectID")]
-    pub object_id: Option<u64>,
-    #[serde(rename = "actionID")]
-    pub action_id: Option<u64>,
-    pub object_index: Option<u64>,
-    pub action_index: Option<u64>,
-}
-
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct IntermediaryAction {
@@ -23,7 +12,8 @@ pub struct IntermediaryAction {
     pub revertable: bool,
     pub d_bit_text: String,
     pub d_bit: bool,
-    pub affects_action: Option<IntermediaryEffect>,
+    pub effects: Vec<EffectYaml>,
+    pub requires: Vec<ConditionYaml>,
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
@@ -38,6 +28,49 @@ pub struct IntermediaryObject {
     pub direction: Option<String>, // TODO Enum
     pub destination: Option<String>,
     pub actions: Option<Vec<IntermediaryAction>>,
+    pub takeable: bool,
+    pub inventory: bool,
+    pub hidden: bool,
+    pub contains: Vec<IntermediaryObject>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IntermediaryResponse {
+    pub text: String,
+    /// Slug of the heading of the next line
+    pub next: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IntermediaryLine {
+    #[serde(rename = "lineID")]
+    pub line_id: u64,
+    pub slug: String,
+    pub text: String,
+    pub responses: Vec<IntermediaryResponse>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IntermediaryReaction {
+    #[serde(rename = "type")]
+    pub ttype: String,
+    pub text: String,
+    pub effects: Vec<EffectYaml>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IntermediaryNpc {
+    #[serde(rename = "npcID")]
+    pub npc_id: u64,
+    pub name: String,
+    pub description: String,
+    pub patrol: Vec<String>,
+    pub dialogue: Vec<IntermediaryLine>,
+    pub reactions: Vec<IntermediaryReaction>,
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Debug)]
@@ -49,7 +82,10 @@ pub struct IntermediaryRoom {
     pub room_description: String,
     pub room_type: String,
     pub biome_type: String,
+    pub start: bool,
+    pub flags: Vec<String>,
     pub objects: Option<Vec<IntermediaryObject>>,
+    pub npcs: Vec<IntermediaryNpc>,
     pub object_ids: Vec<u64>,
     pub dir_obj_ids: Vec<u64>,
 }
@@ -0,0 +1,566 @@
+//! Semantic diff between two configs: rooms, objects and actions are matched by ID, or by
+//! name when their ID changed, and compared field by field. The players of each level and
+//! the translations of each locale are compared too.
+
+use crate::{Action, Config, Object, Player, Room};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Status {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// A field with a different value, named as in the config
+#[derive(Serialize, Debug, PartialEq)]
+pub struct FieldChange {
+    pub field: String,
+    pub old: Value,
+    pub new: Value,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionDiff {
+    #[serde(rename = "actionID")]
+    pub action_id: u64,
+    #[serde(rename = "type")]
+    pub ttype: String,
+    pub status: Status,
+    pub changes: Vec<FieldChange>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectDiff {
+    #[serde(rename = "objID")]
+    pub obj_id: u64,
+    pub obj_description: String,
+    pub status: Status,
+    pub changes: Vec<FieldChange>,
+    pub actions: Vec<ActionDiff>,
+    /// Differences of the objects inside this one
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub contains: Vec<ObjectDiff>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RoomDiff {
+    #[serde(rename = "roomID")]
+    pub room_id: u64,
+    pub room_name: String,
+    pub status: Status,
+    pub changes: Vec<FieldChange>,
+    pub objects: Vec<ObjectDiff>,
+}
+
+/// The player of a level: its start room and ID, and the objects players start with
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayerDiff {
+    pub level_name: String,
+    pub status: Status,
+    pub changes: Vec<FieldChange>,
+    pub inventory: Vec<ObjectDiff>,
+}
+
+/// The translations of a locale, each text ID being a field
+#[derive(Serialize, Debug, PartialEq)]
+pub struct LocaleDiff {
+    pub locale: String,
+    pub status: Status,
+    pub changes: Vec<FieldChange>,
+}
+
+/// Only the rooms that differ, in the order of the new config followed by the removed ones,
+/// then the players and locales that differ
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Diff {
+    pub rooms: Vec<RoomDiff>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub players: Vec<PlayerDiff>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub locales: Vec<LocaleDiff>,
+}
+
+impl Diff {
+    pub fn is_empty(&self) -> bool {
+        self.rooms.is_empty() && self.players.is_empty() && self.locales.is_empty()
+    }
+}
+
+/// Pair the items of `old` and `new` by ID, then the remaining ones by name
+fn pair<'a, T>(
+    old: &'a [T],
+    new: &'a [T],
+    id: impl Fn(&T) -> u64,
+    name: impl Fn(&T) -> String,
+) -> Vec<(Option<&'a T>, Option<&'a T>)> {
+    let mut unmatched: Vec<&T> = old.iter().collect();
+    let mut pairs: Vec<(Option<&T>, Option<&T>)> =
+        new.iter().map(|item| (None, Some(item))).collect();
+
+    for (old, new) in pairs.iter_mut() {
+        let new = new.expect("paired from new");
+        if let Some(index) = unmatched.iter().position(|item| id(item) == id(new)) {
+            *old = Some(unmatched.remove(index));
+        }
+    }
+    for (old, new) in pairs.iter_mut().filter(|(old, _)| old.is_none()) {
+        let new = new.expect("paired from new");
+        if let Some(index) = unmatched.iter().position(|item| name(item) == name(new)) {
+            *old = Some(unmatched.remove(index));
+        }
+    }
+
+    pairs.extend(unmatched.into_iter().map(|item| (Some(item), None)));
+    pairs
+}
+
+/// Compare the fields of two items, leaving out the nested ones listed in `skip`
+fn fields<T: Serialize>(old: &T, new: &T, skip: &[&str]) -> Vec<FieldChange> {
+    let old = serde_json::to_value(old).expect("failed to serialize");
+    let new = serde_json::to_value(new).expect("failed to serialize");
+    let (Value::Object(old), Value::Object(new)) = (old, new) else {
+        return Vec::new();
+    };
+
+    new.into_iter()
+        .filter(|(field, _)| !skip.contains(&field.as_str()))
+        .filter_map(|(field, new)| {
+            let old = old.get(&field).cloned().unwrap_or(Value::Null);
+            (old != new).then_some(FieldChange { field, old, new })
+        })
+        .collect()
+}
+
+/// Where the directional objects of a room lead, e.g. `E -> walking-eagle-pass.md`
+fn exits(room: &Room) -> Value {
+    room.objects
+        .iter()
+        .flatten()
+        .filter_map(|object| {
+            let direction = object.direction.as_deref()?;
+            let destination = object.destination.as_deref().unwrap_or("?");
+            Some(Value::String(format!("{} -> {}", direction, destination)))
+        })
+        .collect()
+}
+
+fn diff_actions(old: &[Action], new: &[Action]) -> Vec<ActionDiff> {
+    let pairs = pair(
+        old,
+        new,
+        |action| action.action_id,
+        |action| action.ttype.clone(),
+    );
+    pairs
+        .into_iter()
+        .filter_map(|pair| {
+            let (status, changes, action) = match pair {
+                (None, Some(new)) => (Status::Added, Vec::new(), new),
+                (Some(old), None) => (Status::Removed, Vec::new(), old),
+                (Some(old), Some(new)) => (Status::Changed, fields(old, new, &[]), new),
+                (None, None) => return None,
+            };
+            if status == Status::Changed && changes.is_empty() {
+                return None;
+            }
+            Some(ActionDiff {
+                action_id: action.action_id,
+                ttype: action.ttype.clone(),
+                status,
+                changes,
+            })
+        })
+        .collect()
+}
+
+fn diff_objects(old: &[Object], new: &[Object]) -> Vec<ObjectDiff> {
+    let pairs = pair(
+        old,
+        new,
+        |object| object.obj_id,
+        |object| object.obj_description.clone(),
+    );
+    pairs
+        .into_iter()
+        .filter_map(|pair| {
+            let (status, changes, actions, contains, object) = match pair {
+                (None, Some(new)) => (Status::Added, Vec::new(), Vec::new(), Vec::new(), new),
+                (Some(old), None) => (Status::Removed, Vec::new(), Vec::new(), Vec::new(), old),
+                (Some(old), Some(new)) => {
+                    let actions = diff_actions(
+                        old.actions.as_deref().unwrap_or_default(),
+                        new.actions.as_deref().unwrap_or_default(),
+                    );
+                    let contains = diff_objects(&old.contains, &new.contains);
+                    let changes = fields(old, new, &["actions", "contains"]);
+                    (Status::Changed, changes, actions, contains, new)
+                }
+                (None, None) => return None,
+            };
+            if status == Status::Changed
+                && changes.is_empty()
+                && actions.is_empty()
+                && contains.is_empty()
+            {
+                return None;
+            }
+            Some(ObjectDiff {
+                obj_id: object.obj_id,
+                obj_description: object.obj_description.clone(),
+                status,
+                changes,
+                actions,
+                contains,
+            })
+        })
+        .collect()
+}
+
+fn rooms(config: &Config) -> Vec<&Room> {
+    config
+        .levels
+        .iter()
+        .flat_map(|level| &level.rooms)
+        .collect()
+}
+
+/// The player of the level named `level_name`
+fn player<'a>(config: &'a Config, level_name: &str) -> Option<&'a Player> {
+    let level = config
+        .levels
+        .iter()
+        .find(|level| level.level_name == level_name)?;
+    level.player.as_ref()
+}
+
+/// The players of the levels of both configs, paired by level name
+fn diff_players(old: &Config, new: &Config) -> Vec<PlayerDiff> {
+    let mut level_names: Vec<&str> = new.levels.iter().map(|l| l.level_name.as_str()).collect();
+    for level in &old.levels {
+        if !level_names.contains(&level.level_name.as_str()) {
+            level_names.push(&level.level_name);
+        }
+    }
+
+    level_names
+        .into_iter()
+        .filter_map(|level_name| {
+            let (status, changes, inventory) =
+                match (player(old, level_name), player(new, level_name)) {
+                    (None, Some(_)) => (Status::Added, Vec::new(), Vec::new()),
+                    (Some(_), None) => (Status::Removed, Vec::new(), Vec::new()),
+                    (Some(old), Some(new)) => {
+                        let changes = fields(old, new, &["inventory"]);
+                        let inventory = diff_objects(&old.inventory, &new.inventory);
+                        if changes.is_empty() && inventory.is_empty() {
+                            return None;
+                        }
+                        (Status::Changed, changes, inventory)
+                    }
+                    (None, None) => return None,
+                };
+            Some(PlayerDiff {
+                level_name: level_name.to_string(),
+                status,
+                changes,
+                inventory,
+            })
+        })
+        .collect()
+}
+
+/// The translations of every locale of both configs, by text ID
+fn diff_locales(old: &Config, new: &Config) -> Vec<LocaleDiff> {
+    let mut locales: Vec<&String> = new.locales.keys().collect();
+    locales.extend(
+        old.locales
+            .keys()
+            .filter(|locale| !new.locales.contains_key(*locale)),
+    );
+
+    locales
+        .into_iter()
+        .filter_map(|locale| {
+            let (status, changes) = match (old.locales.get(locale), new.locales.get(locale)) {
+                (None, Some(_)) => (Status::Added, Vec::new()),
+                (Some(_), None) => (Status::Removed, Vec::new()),
+                (Some(old), Some(new)) => {
+                    let text = |texts: &BTreeMap<String, String>, id: &str| {
+                        texts
+                            .get(id)
+                            .map_or(Value::Null, |text| Value::from(text.as_str()))
+                    };
+                    let mut ids: Vec<&String> = new.keys().collect();
+                    ids.extend(old.keys().filter(|id| !new.contains_key(*id)));
+                    let changes: Vec<FieldChange> = ids
+                        .into_iter()
+                        .filter(|id| old.get(*id) != new.get(*id))
+                        .map(|id| FieldChange {
+                            field: id.clone(),
+                            old: text(old, id),
+                            new: text(new, id),
+                        })
+                        .collect();
+                    if changes.is_empty() {
+                        return None;
+                    }
+                    (Status::Changed, changes)
+                }
+                (None, None) => return None,
+            };
+            Some(LocaleDiff {
+                locale: locale.clone(),
+                status,
+                changes,
+            })
+        })
+        .collect()
+}
+
+pub fn diff(old: &Config, new: &Config) -> Diff {
+    let (old_rooms, new_rooms) = (rooms(old), rooms(new));
+    let pairs = pair(
+        &old_rooms,
+        &new_rooms,
+        |room| room.room_id,
+        |room| room.room_name.clone(),
+    );
+
+    let rooms = pairs
+        .into_iter()
+        .filter_map(|pair| {
+            let (status, changes, objects, room) = match pair {
+                (None, Some(new)) => (Status::Added, Vec::new(), Vec::new(), *new),
+                (Some(old), None) => (Status::Removed, Vec::new(), Vec::new(), *old),
+                (Some(old), Some(new)) => {
+                    let mut changes = fields(*old, *new, &["objects", "objectIds", "dirObjIds"]);
+                    let (old_exits, new_exits) = (exits(old), exits(new));
+                    if old_exits != new_exits {
+                        changes.push(FieldChange {
+                            field: "exits".to_string(),
+                            old: old_exits,
+                            new: new_exits,
+                        });
+                    }
+                    let objects = diff_objects(
+                        old.objects.as_deref().unwrap_or_default(),
+                        new.objects.as_deref().unwrap_or_default(),
+                    );
+                    (Status::Changed, changes, objects, *new)
+                }
+                (None, None) => return None,
+            };
+            if status == Status::Changed && changes.is_empty() && objects.is_empty() {
+                return None;
+            }
+            Some(RoomDiff {
+                room_id: room.room_id,
+                room_name: room.room_name.clone(),
+                status,
+                changes,
+                objects,
+            })
+        })
+        .collect();
+    Diff {
+        rooms,
+        players: diff_players(old, new),
+        locales: diff_locales(old, new),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::try_parse;
+
+    const OLD: &str = "
+version: 1
+levels:
+- levelName: test
+  rooms:
+  - roomID: 1
+    roomName: plain
+    roomDescription: a plain
+    roomType: Plain
+    biomeType: Prarie
+    objectIds: []
+    dirObjIds: [2]
+    objects:
+    - objID: 2
+      type: Path
+      material: Dirt
+      objDescription: a path
+      direction: E
+      destination: barn.md
+      actions:
+      - actionID: 3
+        type: Move
+        enabled: true
+        revertable: false
+        dBitText: you walk
+        dBit: false
+        affectsAction: null
+  - roomID: 4
+    roomName: barn
+    roomDescription: a barn
+    roomType: Barn
+    biomeType: Prarie
+    objectIds: []
+    dirObjIds: []
+    objects: null
+";
+
+    #[test]
+    fn matches_by_id_then_name() {
+        let old = try_parse(OLD).unwrap();
+        let new_str = OLD
+            .replace("roomID: 1", "roomID: 10")
+            .replace("enabled: true", "enabled: false")
+            .replace("destination: barn.md", "destination: forge.md")
+            .replace("roomName: barn", "roomName: forge");
+        let new = try_parse(&new_str).unwrap();
+
+        let diff = diff(&old, &new);
+        assert_eq!(diff.rooms.len(), 2);
+
+        // the ID changed, matched by name
+        let plain = &diff.rooms[0];
+        assert_eq!(plain.status, Status::Changed);
+        let fields: Vec<&str> = plain.changes.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(fields, ["roomID", "exits"]);
+        assert_eq!(plain.objects[0].changes[0].field, "destination");
+        assert_eq!(plain.objects[0].actions[0].changes[0].field, "enabled");
+
+        // the name changed, matched by ID
+        let forge = &diff.rooms[1];
+        assert_eq!(forge.status, Status::Changed);
+        assert_eq!(forge.changes[0].field, "roomName");
+
+        assert!(super::diff(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn reports_added_and_removed_rooms() {
+        let old = try_parse(OLD).unwrap();
+        let mut new = try_parse(OLD).unwrap();
+        new.levels[0].rooms.remove(0);
+
+        let diff = diff(&old, &new);
+        assert_eq!(diff.rooms.len(), 1);
+        assert_eq!(diff.rooms[0].status, Status::Removed);
+        assert_eq!(diff.rooms[0].room_name, "plain");
+
+        let diff = super::diff(&new, &old);
+        assert_eq!(diff.rooms[0].status, Status::Added);
+    }
+
+    #[test]
+    fn reports_player_changes() {
+        let player = "
+  player:
+    startRoom: 1
+    inventory:
+    - objID: 5
+      type: Box
+      material: Wood
+      objDescription: a box
+      direction: null
+      destination: null
+      actions: []
+  rooms:";
+        let old = try_parse(&OLD.replacen("\n  rooms:", player, 1)).unwrap();
+        let moved = OLD.replacen(
+            "\n  rooms:",
+            &player.replace("startRoom: 1", "startRoom: 4"),
+            1,
+        );
+        let new =
+            try_parse(&moved.replace("objDescription: a box", "objDescription: a crate")).unwrap();
+
+        let diff = diff(&old, &new);
+        assert!(diff.rooms.is_empty());
+        let player = &diff.players[0];
+        assert_eq!(
+            (player.level_name.as_str(), player.status),
+            ("test", Status::Changed)
+        );
+        assert_eq!(
+            player.changes,
+            [FieldChange {
+                field: "startRoom".to_string(),
+                old: 1.into(),
+                new: 4.into(),
+            }]
+        );
+        assert_eq!(player.inventory[0].changes[0].field, "objDescription");
+
+        let diff = super::diff(&try_parse(OLD).unwrap(), &old);
+        assert_eq!(diff.players[0].status, Status::Added);
+        assert!(super::diff(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn reports_translation_changes() {
+        let locales = "
+locales:
+  fr:
+    room.1.description: une plaine
+    room.4.description: une grange
+  de:
+    room.1.description: eine Ebene
+";
+        let old = try_parse(&format!("{}{}", OLD, locales)).unwrap();
+        let new = try_parse(&format!(
+            "{}{}",
+            OLD,
+            locales
+                .replace("une plaine", "une prairie")
+                .replace("    room.4.description: une grange\n", "")
+                .replace(
+                    "  de:\n    room.1.description: eine Ebene\n",
+                    "  it:\n    room.1.description: una pianura\n"
+                )
+        ))
+        .unwrap();
+
+        let diff = diff(&old, &new);
+        assert!(diff.rooms.is_empty());
+        let statuses: Vec<(&str, Status)> = diff
+            .locales
+            .iter()
+            .map(|locale| (locale.locale.as_str(), locale.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            [
+                ("fr", Status::Changed),
+                ("it", Status::Added),
+                ("de", Status::Removed)
+            ]
+        );
+        assert_eq!(
+            diff.locales[0].changes,
+            [
+                FieldChange {
+                    field: "room.1.description".to_string(),
+                    old: "une plaine".into(),
+                    new: "une prairie".into(),
+                },
+                FieldChange {
+                    field: "room.4.description".to_string(),
+                    old: "une grange".into(),
+                    new: Value::Null,
+                },
+            ]
+        );
+    }
+}
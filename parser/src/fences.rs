@@ -0,0 +1,140 @@
+//! The YAML blocks fenced in the room markdown files
+
+use crate::schema;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Points at an action, by ID or by position: `room` is the markdown file of the room,
+/// defaulting to the current one, objects and actions are indexed in the order they appear
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionRefYaml {
+    pub room: Option<String>,
+    #[serde(rename = "objectID")]
+    pub object_id: Option<u64>,
+    #[serde(rename = "actionID")]
+    pub action_id: Option<u64>,
+    pub object_index: Option<u64>,
+    pub action_index: Option<u64>,
+}
+
+/// What performing an action does, objects are named by their heading
+/// and rooms by their markdown file
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug, Clone)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum EffectYaml {
+    Enable {
+        action: ActionRefYaml,
+    },
+    Disable {
+        action: ActionRefYaml,
+    },
+    Toggle {
+        action: ActionRefYaml,
+    },
+    /// Performs the action too, when it is enabled and its conditions hold
+    Trigger {
+        action: ActionRefYaml,
+    },
+    /// Moves the player to the room
+    Move {
+        room: String,
+    },
+    /// Brings a hidden object into a room, the room the player is in when not set
+    Spawn {
+        object: String,
+        room: Option<String>,
+    },
+    /// Takes an object out of the world
+    Destroy {
+        object: String,
+    },
+    /// Raises a flag in a room, the room the player is in when not set
+    Raise {
+        flag: String,
+        room: Option<String>,
+    },
+    /// Lowers a flag in a room, the room the player is in when not set
+    Lower {
+        flag: String,
+        room: Option<String>,
+    },
+}
+
+/// Has to hold for an action to be performed, objects are named by their heading
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug, Clone)]
+#[serde(untagged)]
+pub enum ConditionYaml {
+    /// An object in the inventory
+    Holding { holding: String },
+    /// An action performed before
+    Fired { fired: ActionRefYaml },
+    /// A flag raised in the room the player is in
+    Flag { flag: String },
+}
+
+/// Fenced below the room description
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RoomYaml {
+    #[schemars(schema_with = "schema::room_type")]
+    pub room_type: String,
+    pub biome_type: String,
+    /// Players start in this room
+    pub start: Option<bool>,
+    /// Flags raised when the level starts
+    pub flags: Option<Vec<String>>,
+}
+
+/// Fenced below an object description
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectYaml {
+    #[schemars(schema_with = "schema::direction_name")]
+    pub direction: Option<String>,
+    #[serde(rename = "type")]
+    #[schemars(schema_with = "schema::object_type")]
+    pub ttype: String,
+    #[schemars(schema_with = "schema::material")]
+    pub material: String,
+    /// Can be picked up and carried in the inventory
+    pub takeable: Option<bool>,
+    /// Players start with this object instead of finding it in the room
+    pub inventory: Option<bool>,
+    /// Not in the room until an effect spawns it
+    pub hidden: Option<bool>,
+}
+
+/// Fenced below an action description
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionYaml {
+    #[serde(rename = "type")]
+    #[schemars(schema_with = "schema::action_type")]
+    pub ttype: String, // TODO enum?
+    pub enabled: Option<bool>,
+    pub revertable: Option<bool>,
+    pub d_bit: Option<bool>,
+    /// Enables the action, the same as an `enable` effect applied first
+    pub affects: Option<ActionRefYaml>,
+    /// Applied in order when the action is performed
+    pub effects: Option<Vec<EffectYaml>>,
+    /// All have to hold for the action to be performed
+    pub requires: Option<Vec<ConditionYaml>>,
+}
+
+/// Fenced below the description of a character, `npc: true` tells it from an object
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct NpcYaml {
+    pub npc: bool,
+    /// Markdown files of the rooms the character walks through in turn
+    pub patrol: Option<Vec<String>>,
+}
+
+/// Fenced below the text of a reaction of a character
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ReactionYaml {
+    pub effects: Option<Vec<EffectYaml>>,
+}
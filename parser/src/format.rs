@@ -0,0 +1,315 @@
+//! Reading and writing the config as YAML, JSON, TOML or RON, picked from the file extension
+
+use crate::{merge, migrate, Config};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Yaml,
+    Json,
+    Toml,
+    Ron,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    /// The extension does not match any format
+    UnknownFormat(String),
+    Parse(String),
+    /// Written by a newer pray_engine
+    Version(u64),
+    /// In a file included by another one
+    Include(PathBuf, Box<Error>),
+    /// Files including each other, from the first one back to it
+    IncludeCycle(Vec<PathBuf>),
+    /// The same rooms, objects or actions defined by several files
+    Conflicts(Vec<merge::Conflict>),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{}", e),
+            Error::UnknownFormat(extension) => write!(
+                f,
+                "unknown config format `{}`, expected one of: yml, yaml, json, toml, ron",
+                extension
+            ),
+            Error::Parse(e) => write!(f, "{}", e),
+            Error::Version(version) => write!(
+                f,
+                "config version {} is newer than the supported version {}, update pray",
+                version,
+                migrate::VERSION
+            ),
+            Error::Include(path, e) => write!(f, "{}: {}", path.to_string_lossy(), e),
+            Error::IncludeCycle(paths) => write!(
+                f,
+                "include cycle: {}",
+                paths
+                    .iter()
+                    .map(|path| path.to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join(" -> ")
+            ),
+            Error::Conflicts(conflicts) => {
+                write!(f, "conflicting definitions:")?;
+                for conflict in conflicts {
+                    write!(f, "\n  {}", conflict)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl Format {
+    pub fn from_path(path: &Path) -> Result<Format, Error> {
+        let extension = path
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        match extension.as_str() {
+            "yml" | "yaml" => Ok(Format::Yaml),
+            "json" => Ok(Format::Json),
+            "toml" => Ok(Format::Toml),
+            "ron" => Ok(Format::Ron),
+            _ => Err(Error::UnknownFormat(extension)),
+        }
+    }
+
+    /// Parse a config, migrating it when it was written for an older version
+    pub fn parse(self, str: &str) -> Result<Config, Error> {
+        let mut value: Value = self.deserialize(str)?;
+        if migrate::version(&value) == migrate::VERSION {
+            // straight from the text, so errors keep their line numbers
+            return self.deserialize(str);
+        }
+        migrate::migrate(&mut value)?;
+        serde_json::from_value(value).map_err(|e| Error::Parse(e.to_string()))
+    }
+
+    /// Parse a document as any serde type, e.g. a `Value` to migrate
+    pub fn deserialize<T: DeserializeOwned>(self, str: &str) -> Result<T, Error> {
+        let parsed = match self {
+            Format::Yaml => serde_yml::from_str(str).map_err(|e| e.to_string()),
+            Format::Json => serde_json::from_str(str).map_err(|e| e.to_string()),
+            Format::Toml => toml::from_str(str).map_err(|e| e.to_string()),
+            Format::Ron => ron::from_str(str).map_err(|e| e.to_string()),
+        };
+        parsed.map_err(Error::Parse)
+    }
+
+    pub fn serialize(self, config: &Config) -> String {
+        match self {
+            Format::Yaml => crate::serialize(config),
+            Format::Json => {
+                serde_json::to_string_pretty(config).expect("failed to serialize json") + "\n"
+            }
+            Format::Toml => {
+                let value = serde_json::to_value(config).expect("failed to serialize toml");
+                toml::to_string_pretty(&tomlable(value)).expect("failed to serialize toml")
+            }
+            Format::Ron => {
+                let pretty = ron::ser::PrettyConfig::default();
+                ron::ser::to_string_pretty(config, pretty).expect("failed to serialize ron") + "\n"
+            }
+        }
+    }
+}
+
+/// TOML has neither null nor integers above i64::MAX: drop the nulls, turn the big IDs into strings
+fn tomlable(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(key, value)| (key, tomlable(value)))
+                .collect(),
+        ),
+        Value::Array(values) => Value::Array(values.into_iter().map(tomlable).collect()),
+        Value::Number(number) if number.as_i64().is_none() && number.is_u64() => {
+            Value::String(number.to_string())
+        }
+        value => value,
+    }
+}
+
+pub fn load(path: &Path) -> Result<Config, Error> {
+    let format = Format::from_path(path)?;
+    let str = fs::read_to_string(path).map_err(Error::Io)?;
+    format.parse(&str)
+}
+
+pub fn save(path: &Path, config: &Config) -> Result<(), Error> {
+    let format = Format::from_path(path)?;
+    fs::write(path, format.serialize(config)).map_err(Error::Io)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Action, Condition, Effect, Level, Line, Npc, Object, Reaction, Response, Room};
+    use std::collections::BTreeMap;
+
+    const FORMATS: [Format; 4] = [Format::Yaml, Format::Json, Format::Toml, Format::Ron];
+
+    fn config() -> Config {
+        Config {
+            version: migrate::VERSION,
+            include: Vec::new(),
+            locales: BTreeMap::from([(
+                "fr".into(),
+                BTreeMap::from([(
+                    "room.18446744073709551614.description".into(),
+                    "premier".into(),
+                )]),
+            )]),
+            levels: vec![Level {
+                level_name: "test".into(),
+                player: None,
+                rooms: vec![Room {
+                    room_id: u64::MAX - 1,
+                    room_name: "test1".into(),
+                    room_description: "first line\nsecond \"line\"".into(),
+                    room_type: "Mountains".into(),
+                    biome_type: "Alpine".into(),
+                    objects: Some(vec![Object {
+                        obj_id: 42,
+                        ttype: "Path".into(),
+                        material: "Dirt".into(),
+                        obj_description: "a path".into(),
+                        direction: Some("N".into()),
+                        destination: None,
+                        actions: Some(vec![Action {
+                            action_id: 1 << 63,
+                            ttype: "Move".into(),
+                            enabled: true,
+                            revertable: false,
+                            d_bit_text: "you walk".into(),
+                            d_bit: false,
+                            effects: vec![
+                                Effect::Toggle { action: u64::MAX },
+                                Effect::Spawn {
+                                    object: 42,
+                                    room: None,
+                                },
+                                Effect::Raise {
+                                    flag: "dark".into(),
+                                    room: Some(u64::MAX - 1),
+                                },
+                            ],
+                            requires: vec![
+                                Condition::Holding { holding: u64::MAX },
+                                Condition::Fired { fired: 7 },
+                                Condition::Flag {
+                                    flag: "dark".into(),
+                                },
+                            ],
+                        }]),
+                        takeable: false,
+                        hidden: false,
+                        contains: Vec::new(),
+                    }]),
+                    object_ids: vec![42, u64::MAX],
+                    dir_obj_ids: vec![],
+                    flags: vec!["dark".into()],
+                    npcs: vec![Npc {
+                        npc_id: u64::MAX - 2,
+                        name: "Eli".into(),
+                        description: "a farmer".into(),
+                        dialogue: vec![Line {
+                            line_id: 1 << 62,
+                            text: "howdy".into(),
+                            responses: vec![Response {
+                                text: "bye".into(),
+                                next: None,
+                            }],
+                        }],
+                        reactions: [(
+                            "Fight".to_string(),
+                            Reaction {
+                                text: "he swings a pitchfork".into(),
+                                effects: vec![Effect::Move { room: u64::MAX - 1 }],
+                            },
+                        )]
+                        .into(),
+                        patrol: vec![u64::MAX - 1],
+                    }],
+                }],
+            }],
+        }
+    }
+
+    #[test]
+    fn round_trips() {
+        let config = config();
+        for format in FORMATS {
+            let str = format.serialize(&config);
+            let parsed = format
+                .parse(&str)
+                .unwrap_or_else(|e| panic!("{:?}: {}", format, e));
+            assert_eq!(parsed, config, "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn written_configs_match_the_schema() {
+        let schema = serde_json::to_value(schemars::schema_for!(Config)).unwrap();
+        let validator = jsonschema::validator_for(&schema).unwrap();
+        for format in FORMATS {
+            let written = format.serialize(&config());
+            let value: serde_json::Value = format.deserialize(&written).unwrap();
+            let errors: Vec<String> = validator
+                .iter_errors(&value)
+                .map(|e| format!("{} at {}", e, e.instance_path))
+                .collect();
+            assert!(errors.is_empty(), "{:?}: {:#?}", format, errors);
+        }
+    }
+
+    #[test]
+    fn converts_between_formats() {
+        let mut config = config();
+        for from in FORMATS {
+            for to in FORMATS {
+                let str = to.serialize(&from.parse(&from.serialize(&config)).unwrap());
+                config = to.parse(&str).unwrap();
+            }
+        }
+        assert_eq!(config, self::config());
+    }
+
+    #[test]
+    fn detects_format_from_extension() {
+        assert_eq!(
+            Format::from_path(Path::new("a/config.yml")).unwrap(),
+            Format::Yaml
+        );
+        assert_eq!(
+            Format::from_path(Path::new("config.YAML")).unwrap(),
+            Format::Yaml
+        );
+        assert_eq!(
+            Format::from_path(Path::new("config.json")).unwrap(),
+            Format::Json
+        );
+        assert_eq!(
+            Format::from_path(Path::new("config.toml")).unwrap(),
+            Format::Toml
+        );
+        assert_eq!(
+            Format::from_path(Path::new("config.ron")).unwrap(),
+            Format::Ron
+        );
+        assert!(Format::from_path(Path::new("config.txt")).is_err());
+    }
+}
@@ -0,0 +1,39 @@
+//! IDs are hashes spanning the whole u64 range, TOML integers stop at i64::MAX so they are
+//! written there as strings. Reading accepts both.
+
+use serde::{Deserialize, Deserializer};
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Id {
+    Number(u64),
+    Text(String),
+}
+
+impl Id {
+    fn value<E: serde::de::Error>(self) -> Result<u64, E> {
+        match self {
+            Id::Number(id) => Ok(id),
+            Id::Text(str) => str
+                .parse()
+                .map_err(|_| E::custom(format!("invalid ID `{}`", str))),
+        }
+    }
+}
+
+pub fn one<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    Id::deserialize(deserializer)?.value()
+}
+
+pub fn option<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u64>, D::Error> {
+    Option::<Id>::deserialize(deserializer)?
+        .map(Id::value)
+        .transpose()
+}
+
+pub fn many<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u64>, D::Error> {
+    Vec::<Id>::deserialize(deserializer)?
+        .into_iter()
+        .map(Id::value)
+        .collect()
+}
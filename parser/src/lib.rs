@@ -1,73 +1,387 @@
+pub mod diff;
+pub mod fences;
+pub mod format;
+mod id;
+pub mod locale;
+pub mod merge;
+pub mod migrate;
+pub mod partition;
+pub mod schema;
+pub mod simulate;
+pub mod stats;
+pub mod text;
+pub mod validate;
+
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
-#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
-#[serde(rename_all = "camelCase")]
-pub struct Effect {
-    #[serde(rename = "roomID")]
-    pub room_id: Option<u64>,
-    #[serde(rename = "objectID")]
-    pub object_id: Option<u64>,
-    #[serde(rename = "actionID")]
-    pub action_id: u64,
+/// What performing an action does besides describing it, see `simulate`
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug, Clone)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum Effect {
+    Enable {
+        #[serde(deserialize_with = "id::one")]
+        #[schemars(schema_with = "schema::id")]
+        action: u64,
+    },
+    Disable {
+        #[serde(deserialize_with = "id::one")]
+        #[schemars(schema_with = "schema::id")]
+        action: u64,
+    },
+    Toggle {
+        #[serde(deserialize_with = "id::one")]
+        #[schemars(schema_with = "schema::id")]
+        action: u64,
+    },
+    /// Performs the action too, wherever its object is, when it is enabled and its conditions hold
+    Trigger {
+        #[serde(deserialize_with = "id::one")]
+        #[schemars(schema_with = "schema::id")]
+        action: u64,
+    },
+    /// Moves the player to the room
+    Move {
+        #[serde(deserialize_with = "id::one")]
+        #[schemars(schema_with = "schema::id")]
+        room: u64,
+    },
+    /// Brings a hidden object into a room, the room the player is in when not set
+    Spawn {
+        #[serde(deserialize_with = "id::one")]
+        #[schemars(schema_with = "schema::id")]
+        object: u64,
+        #[serde(
+            default,
+            deserialize_with = "id::option",
+            skip_serializing_if = "Option::is_none"
+        )]
+        #[schemars(schema_with = "schema::id_or_null")]
+        room: Option<u64>,
+    },
+    /// Takes an object out of the world, out of the inventory too
+    Destroy {
+        #[serde(deserialize_with = "id::one")]
+        #[schemars(schema_with = "schema::id")]
+        object: u64,
+    },
+    /// Raises a flag in a room, the room the player is in when not set
+    Raise {
+        flag: String,
+        #[serde(
+            default,
+            deserialize_with = "id::option",
+            skip_serializing_if = "Option::is_none"
+        )]
+        #[schemars(schema_with = "schema::id_or_null")]
+        room: Option<u64>,
+    },
+    /// Lowers a flag in a room, the room the player is in when not set
+    Lower {
+        flag: String,
+        #[serde(
+            default,
+            deserialize_with = "id::option",
+            skip_serializing_if = "Option::is_none"
+        )]
+        #[schemars(schema_with = "schema::id_or_null")]
+        room: Option<u64>,
+    },
 }
 
-#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+impl Effect {
+    /// `actionID` of the action the effect changes, if any
+    pub fn action(&self) -> Option<u64> {
+        match self {
+            Effect::Enable { action }
+            | Effect::Disable { action }
+            | Effect::Toggle { action }
+            | Effect::Trigger { action } => Some(*action),
+            _ => None,
+        }
+    }
+
+    /// The `kind` the effect is written with, e.g. `enable`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Effect::Enable { .. } => "enable",
+            Effect::Disable { .. } => "disable",
+            Effect::Toggle { .. } => "toggle",
+            Effect::Trigger { .. } => "trigger",
+            Effect::Move { .. } => "move",
+            Effect::Spawn { .. } => "spawn",
+            Effect::Destroy { .. } => "destroy",
+            Effect::Raise { .. } => "raise",
+            Effect::Lower { .. } => "lower",
+        }
+    }
+}
+
+/// Has to hold for an action to be performed, see `simulate`
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug, Clone)]
+#[serde(untagged)]
+pub enum Condition {
+    /// `objID` of an object in the inventory
+    Holding {
+        #[serde(deserialize_with = "id::one")]
+        #[schemars(schema_with = "schema::id")]
+        holding: u64,
+    },
+    /// `actionID` of an action performed before
+    Fired {
+        #[serde(deserialize_with = "id::one")]
+        #[schemars(schema_with = "schema::id")]
+        fired: u64,
+    },
+    /// Flag raised in the room the player is in
+    Flag { flag: String },
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Action {
-    #[serde(rename = "actionID")]
+    #[serde(rename = "actionID", deserialize_with = "id::one")]
+    #[schemars(schema_with = "schema::id")]
     pub action_id: u64,
     #[serde(rename = "type")]
+    #[schemars(schema_with = "schema::action_type")]
     pub ttype: String, // TODO enum?
     pub enabled: bool,
     pub revertable: bool,
     pub d_bit_text: String,
     pub d_bit: bool,
-    pub affects_action: Option<u64>,
+    /// Applied in order when the action is performed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub effects: Vec<Effect>,
+    /// All have to hold for the action to be performed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub requires: Vec<Condition>,
 }
 
-#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Object {
-    #[serde(rename = "objID")]
+    #[serde(rename = "objID", deserialize_with = "id::one")]
+    #[schemars(schema_with = "schema::id")]
     pub obj_id: u64,
     #[serde(rename = "type")]
+    #[schemars(schema_with = "schema::object_type")]
     pub ttype: String, // TODO enum ?
+    #[schemars(schema_with = "schema::material")]
     pub material: String, // TODO Enum
     pub obj_description: String,
+    #[schemars(schema_with = "schema::direction")]
     pub direction: Option<String>, // TODO Enu
     pub destination: Option<String>,
     pub actions: Option<Vec<Action>>,
+    /// Can be picked up and carried in the inventory
+    #[serde(default)]
+    pub takeable: bool,
+    /// Not in the world until an effect spawns it
+    #[serde(default)]
+    pub hidden: bool,
+    /// Objects inside this one, e.g. the matches in a box
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub contains: Vec<Object>,
+}
+
+/// Where an object starts
+#[derive(Serialize, PartialEq, Debug, Clone, Copy)]
+#[serde(tag = "kind", content = "id", rename_all = "camelCase")]
+pub enum Location {
+    /// `roomID` of the room
+    Room(u64),
+    /// `objID` of the containing object
+    Container(u64),
+    Inventory,
+}
+
+/// An object with where it starts, as listed by `Level::objects`
+#[derive(Serialize, Debug)]
+pub struct Placed<'a> {
+    pub location: Location,
+    #[serde(flatten)]
+    pub object: &'a Object,
 }
 
-#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Room {
-    #[serde(rename = "roomID")]
+    #[serde(rename = "roomID", deserialize_with = "id::one")]
+    #[schemars(schema_with = "schema::id")]
     pub room_id: u64,
     pub room_name: String,
     pub room_description: String,
+    #[schemars(schema_with = "schema::room_type")]
     pub room_type: String,
     pub biome_type: String,
     pub objects: Option<Vec<Object>>,
+    #[serde(deserialize_with = "id::many")]
+    #[schemars(schema_with = "schema::ids")]
     pub object_ids: Vec<u64>,
+    #[serde(deserialize_with = "id::many")]
+    #[schemars(schema_with = "schema::ids")]
     pub dir_obj_ids: Vec<u64>,
+    /// Flags raised when the level starts
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub flags: Vec<String>,
+    /// Characters starting in the room
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub npcs: Vec<Npc>,
+}
+
+/// A non player character, see `Room::npcs`
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Npc {
+    #[serde(rename = "npcID", deserialize_with = "id::one")]
+    #[schemars(schema_with = "schema::id")]
+    pub npc_id: u64,
+    pub name: String,
+    pub description: String,
+    /// What the character says, the conversation starts with the first line
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dialogue: Vec<Line>,
+    /// How the character reacts to the player performing an action on them, by action type
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub reactions: BTreeMap<String, Reaction>,
+    /// `roomID`s of the rooms the character walks through in turn, back to the first one
+    #[serde(
+        default,
+        deserialize_with = "id::many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    #[schemars(schema_with = "schema::ids")]
+    pub patrol: Vec<u64>,
+}
+
+/// A line of dialogue with the answers players can pick
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Line {
+    #[serde(rename = "lineID", deserialize_with = "id::one")]
+    #[schemars(schema_with = "schema::id")]
+    pub line_id: u64,
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub responses: Vec<Response>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Response {
+    pub text: String,
+    /// `lineID` of the line the character answers with, the conversation ends when not set
+    #[serde(
+        default,
+        deserialize_with = "id::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(schema_with = "schema::id_or_null")]
+    pub next: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Reaction {
+    pub text: String,
+    /// Applied in order, like the effects of an action
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub effects: Vec<Effect>,
 }
 
-#[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// How players enter a level
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Player {
+    /// Player the level is set up for
+    #[serde(rename = "playerID", default = "default_player_id")]
+    pub player_id: u64,
+    /// `roomID` of the room players start in, the first room when not set
+    #[serde(default, deserialize_with = "id::option")]
+    #[schemars(schema_with = "schema::id_or_null")]
+    pub start_room: Option<u64>,
+    /// Objects players start with
+    #[serde(default)]
+    pub inventory: Vec<Object>,
+}
+
+fn default_player_id() -> u64 {
+    23
+}
+
+impl Default for Player {
+    fn default() -> Self {
+        Player {
+            player_id: default_player_id(),
+            start_room: None,
+            inventory: Vec::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Level {
     pub level_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub player: Option<Player>,
     pub rooms: Vec<Room>,
 }
 
-#[derive(Serialize, Deserialize, PartialEq, Debug)]
+fn place<'a>(location: Location, objects: &'a [Object], placed: &mut Vec<Placed<'a>>) {
+    for object in objects {
+        placed.push(Placed { location, object });
+        place(Location::Container(object.obj_id), &object.contains, placed);
+    }
+}
+
+impl Level {
+    /// Every object of the level, in rooms, in containers or in the inventory
+    pub fn objects(&self) -> Vec<Placed<'_>> {
+        let mut placed = Vec::new();
+        for room in &self.rooms {
+            let objects = room.objects.as_deref().unwrap_or_default();
+            place(Location::Room(room.room_id), objects, &mut placed);
+        }
+        if let Some(player) = &self.player {
+            place(Location::Inventory, &player.inventory, &mut placed);
+        }
+        placed
+    }
+
+    /// The room players start in
+    pub fn start_room(&self) -> Option<&Room> {
+        match self.player.as_ref().and_then(|player| player.start_room) {
+            Some(id) => self.rooms.iter().find(|room| room.room_id == id),
+            None => self.rooms.first(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Config {
+    /// Version of the config shape, see `migrate`
+    #[serde(default)]
+    pub version: u64,
+    /// Partial configs merged into this one, relative to this file, see `merge`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
     pub levels: Vec<Level>,
+    /// Translated texts by locale then by text ID, see `locale`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub locales: BTreeMap<String, BTreeMap<String, String>>,
 }
 
-pub fn parse(str: &String) -> Config {
-    serde_yml::from_str(str).expect("failed to parse yaml config")
+pub fn parse(str: &str) -> Config {
+    try_parse(str).expect("failed to parse yaml config")
+}
+
+/// Parse a YAML config, migrating it when it was written for an older version
+pub fn try_parse(str: &str) -> Result<Config, format::Error> {
+    format::Format::Yaml.parse(str)
 }
 
 pub fn serialize(config: &Config) -> String {
@@ -81,20 +395,77 @@ mod tests {
     #[test]
     fn it_works() {
         let config = Config {
+            version: migrate::VERSION,
+            include: Vec::new(),
+            locales: BTreeMap::new(),
             levels: vec![Level {
                 level_name: "test".into(),
+                player: None,
                 rooms: vec![Room {
                     room_id: 1,
                     room_type: "Mountains".into(),
+                    biome_type: "Alpine".into(),
                     objects: None,
                     room_description: "fdd".into(),
                     room_name: "test1".into(),
                     object_ids: [].into(),
                     dir_obj_ids: [].into(),
+                    flags: Vec::new(),
+                    npcs: Vec::new(),
                 }],
             }],
         };
         let str = serialize(&config);
         assert_eq!(parse(&str), config);
     }
+
+    #[test]
+    fn places_objects() {
+        let object = |obj_id, contains| Object {
+            obj_id,
+            ttype: "Box".into(),
+            material: "Wood".into(),
+            obj_description: String::new(),
+            direction: None,
+            destination: None,
+            actions: None,
+            takeable: true,
+            hidden: false,
+            contains,
+        };
+        let level = Level {
+            level_name: "test".into(),
+            player: Some(Player {
+                inventory: vec![object(4, vec![])],
+                ..Player::default()
+            }),
+            rooms: vec![Room {
+                room_id: 1,
+                room_type: "Store".into(),
+                biome_type: "Prarie".into(),
+                objects: Some(vec![object(2, vec![object(3, vec![])])]),
+                room_description: String::new(),
+                room_name: "store".into(),
+                object_ids: vec![2],
+                dir_obj_ids: vec![],
+                flags: Vec::new(),
+                npcs: Vec::new(),
+            }],
+        };
+
+        let locations: Vec<(u64, Location)> = level
+            .objects()
+            .iter()
+            .map(|placed| (placed.object.obj_id, placed.location))
+            .collect();
+        assert_eq!(
+            locations,
+            [
+                (2, Location::Room(1)),
+                (3, Location::Container(2)),
+                (4, Location::Inventory),
+            ]
+        );
+        assert_eq!(level.start_room().unwrap().room_id, 1);
+    }
 }
@@ -0,0 +1,202 @@
+//! Translations of the texts players read.
+//! Every text has a stable ID built from the ID of what it belongs to, e.g. `object.42.description`,
+//! and `Config::locales` holds the translations of each locale by text ID.
+
+use crate::{Config, Level};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Locale the texts of the config itself are written in
+pub const SOURCE: &str = "en";
+
+/// What a text belongs to, displayed as its text ID
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TextId {
+    /// `roomDescription` of a room
+    Room(u64),
+    /// `objDescription` of an object
+    Object(u64),
+    /// `dBitText` of an action
+    Action(u64),
+    /// Description of a character
+    Npc(u64),
+    /// Text of a line of dialogue
+    Line(u64),
+    /// Answer to a line of dialogue, by index
+    Response(u64, usize),
+    /// Reaction of a character to an action type
+    Reaction(u64, String),
+}
+
+impl fmt::Display for TextId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextId::Room(id) => write!(f, "room.{}.description", id),
+            TextId::Object(id) => write!(f, "object.{}.description", id),
+            TextId::Action(id) => write!(f, "action.{}.text", id),
+            TextId::Npc(id) => write!(f, "npc.{}.description", id),
+            TextId::Line(id) => write!(f, "line.{}.text", id),
+            TextId::Response(id, index) => write!(f, "line.{}.response.{}", id, index),
+            TextId::Reaction(id, ttype) => write!(f, "npc.{}.reaction.{}", id, ttype),
+        }
+    }
+}
+
+/// Every text of a level by text ID, in the source locale
+pub fn level_texts(level: &Level) -> BTreeMap<String, String> {
+    let mut texts = BTreeMap::new();
+    let mut text = |id: TextId, text: &str| {
+        // nothing to translate
+        if !text.is_empty() {
+            texts.insert(id.to_string(), text.to_string());
+        }
+    };
+    for room in &level.rooms {
+        text(TextId::Room(room.room_id), &room.room_description);
+        for npc in &room.npcs {
+            text(TextId::Npc(npc.npc_id), &npc.description);
+            for line in &npc.dialogue {
+                text(TextId::Line(line.line_id), &line.text);
+                for (index, response) in line.responses.iter().enumerate() {
+                    text(TextId::Response(line.line_id, index), &response.text);
+                }
+            }
+            for (ttype, reaction) in &npc.reactions {
+                text(TextId::Reaction(npc.npc_id, ttype.clone()), &reaction.text);
+            }
+        }
+    }
+    for placed in level.objects() {
+        let object = placed.object;
+        text(TextId::Object(object.obj_id), &object.obj_description);
+        for action in object.actions.iter().flatten() {
+            text(TextId::Action(action.action_id), &action.d_bit_text);
+        }
+    }
+    texts
+}
+
+/// Every text of the config by text ID, in the source locale
+pub fn texts(config: &Config) -> BTreeMap<String, String> {
+    config.levels.iter().flat_map(level_texts).collect()
+}
+
+/// Every locale of the config, the source locale first
+pub fn locales(config: &Config) -> Vec<&str> {
+    let translated = config.locales.keys().map(String::as_str);
+    let mut locales = vec![SOURCE];
+    locales.extend(translated.filter(|locale| *locale != SOURCE));
+    locales
+}
+
+/// The texts of `locale` by text ID, texts without a translation stay in the source locale
+pub fn table(config: &Config, locale: &str) -> BTreeMap<String, String> {
+    let mut texts = texts(config);
+    if let Some(translations) = config.locales.get(locale) {
+        for (id, text) in texts.iter_mut() {
+            if let Some(translation) = translations.get(id) {
+                text.clone_from(translation);
+            }
+        }
+    }
+    texts
+}
+
+/// How much of the world a locale translates
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Coverage {
+    pub locale: String,
+    /// Number of texts of the world
+    pub total: usize,
+    /// IDs of the texts without a translation
+    pub missing: Vec<String>,
+    /// IDs of translated texts the world does not have, e.g. of a removed object
+    pub unknown: Vec<String>,
+}
+
+impl Coverage {
+    pub fn translated(&self) -> usize {
+        self.total - self.missing.len()
+    }
+}
+
+/// The coverage of every translated locale
+pub fn coverage(config: &Config) -> Vec<Coverage> {
+    let texts = texts(config);
+    config
+        .locales
+        .iter()
+        .map(|(locale, translations)| Coverage {
+            locale: locale.clone(),
+            total: texts.len(),
+            missing: texts
+                .keys()
+                .filter(|id| !translations.contains_key(*id))
+                .cloned()
+                .collect(),
+            unknown: translations
+                .keys()
+                .filter(|id| !texts.contains_key(*id))
+                .cloned()
+                .collect(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_missing_translations() {
+        let config = crate::parse(
+            r#"
+levels:
+- levelName: test
+  rooms:
+  - roomID: 1
+    roomName: plain
+    roomDescription: a plain
+    roomType: Plain
+    biomeType: Prarie
+    objectIds: [2]
+    dirObjIds: []
+    objects:
+    - objID: 2
+      type: Bale
+      material: Hay
+      objDescription: a bale
+      direction: null
+      destination: null
+      actions:
+      - actionID: 3
+        type: Burn
+        enabled: true
+        revertable: false
+        dBitText: it burns
+        dBit: true
+locales:
+  fr:
+    room.1.description: une plaine
+    action.3.text: elle brûle
+    object.9.description: une fourche
+"#,
+        );
+        assert_eq!(locales(&config), ["en", "fr"]);
+        assert_eq!(
+            coverage(&config),
+            [Coverage {
+                locale: "fr".into(),
+                total: 3,
+                missing: vec!["object.2.description".into()],
+                unknown: vec!["object.9.description".into()],
+            }]
+        );
+
+        let table = table(&config, "fr");
+        assert_eq!(table["room.1.description"], "une plaine");
+        assert_eq!(table["object.2.description"], "a bale");
+        assert!(!table.contains_key("object.9.description"));
+    }
+}
@@ -0,0 +1,292 @@
+//! Composing one world from partial configs, each owned by different writers.
+//! Levels with the same name are joined, rooms, objects, actions and npcs must be unique,
+//! and so must translations of a text into a locale.
+
+use crate::format::{self, Error};
+use crate::{Config, Level, Location, Placed};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Something defined twice, by two sources or by the same one, the sources being file or folder names
+#[derive(Debug, PartialEq)]
+pub struct Conflict {
+    pub what: String,
+    pub first: String,
+    pub second: String,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.first == self.second {
+            return write!(f, "{} is defined twice in {}", self.what, self.first);
+        }
+        write!(
+            f,
+            "{} is defined in both {} and {}",
+            self.what, self.first, self.second
+        )
+    }
+}
+
+/// Merge partial configs, each given with the name of where it comes from
+pub fn merge(parts: Vec<(String, Config)>) -> Result<Config, Error> {
+    let mut conflicts = Vec::new();
+    let mut seen: HashMap<String, String> = HashMap::new();
+    // false when `what` was already defined, by another source or the same one
+    let mut claim = |what: String, source: &str| match seen.insert(what.clone(), source.into()) {
+        Some(first) => {
+            conflicts.push(Conflict {
+                what,
+                first,
+                second: source.to_string(),
+            });
+            false
+        }
+        _ => true,
+    };
+
+    let mut levels: Vec<Level> = Vec::new();
+    let mut locales: BTreeMap<String, BTreeMap<String, String>> = BTreeMap::new();
+    for (source, config) in parts {
+        for (locale, texts) in config.locales {
+            for (id, text) in texts {
+                if claim(format!("text `{}` in locale {}", id, locale), &source) {
+                    locales.entry(locale.clone()).or_default().insert(id, text);
+                }
+            }
+        }
+        for level in config.levels {
+            if level.player.is_some() {
+                claim(format!("player of level `{}`", level.level_name), &source);
+            }
+            // the objects of a duplicated room are duplicated as well, no need to list them
+            let mut skipped = HashSet::new();
+            for room in &level.rooms {
+                let id = claim(format!("room ID {}", room.room_id), &source);
+                let name = claim(format!("room `{}`", room.room_name), &source);
+                if !(id && name) {
+                    skipped.insert(room.room_id);
+                }
+            }
+            let rooms = level.rooms.iter().filter(|r| !skipped.contains(&r.room_id));
+            for npc in rooms.flat_map(|room| &room.npcs) {
+                claim(format!("npc ID {}", npc.npc_id), &source);
+            }
+            for Placed { location, object } in level.objects() {
+                let container = match location {
+                    Location::Room(id) | Location::Container(id) => Some(id),
+                    Location::Inventory => None,
+                };
+                if container.is_some_and(|id| skipped.contains(&id)) {
+                    skipped.insert(object.obj_id);
+                    continue;
+                }
+                claim(format!("object ID {}", object.obj_id), &source);
+                for action in object.actions.iter().flatten() {
+                    claim(format!("action ID {}", action.action_id), &source);
+                }
+            }
+            match levels.iter_mut().find(|l| l.level_name == level.level_name) {
+                Some(merged) => {
+                    merged.player = merged.player.take().or(level.player);
+                    merged.rooms.extend(level.rooms);
+                }
+                None => levels.push(level),
+            }
+        }
+    }
+
+    if !conflicts.is_empty() {
+        return Err(Error::Conflicts(conflicts));
+    }
+    Ok(Config {
+        version: crate::migrate::VERSION,
+        include: Vec::new(),
+        levels,
+        locales,
+    })
+}
+
+/// Load a config and, recursively, the configs it includes, as parts to `merge`
+pub fn load_parts(path: &Path) -> Result<Vec<(String, Config)>, Error> {
+    let mut parts = Vec::new();
+    collect(path, &mut Vec::new(), &mut parts)?;
+    Ok(parts)
+}
+
+fn collect(
+    path: &Path,
+    stack: &mut Vec<PathBuf>,
+    parts: &mut Vec<(String, Config)>,
+) -> Result<(), Error> {
+    let identity = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if let Some(start) = stack.iter().position(|p| *p == identity) {
+        let mut cycle = stack[start..].to_vec();
+        cycle.push(identity);
+        return Err(Error::IncludeCycle(cycle));
+    }
+
+    let mut config = format::load(path)?;
+    let includes = std::mem::take(&mut config.include);
+    parts.push((path.to_string_lossy().into_owned(), config));
+
+    stack.push(identity);
+    let parent = path.parent().unwrap_or(Path::new(""));
+    for include in includes {
+        let include = parent.join(include);
+        collect(&include, stack, parts).map_err(|e| match e {
+            // report problems of nested files where they are
+            e @ (Error::Include(..) | Error::IncludeCycle(_) | Error::Conflicts(_)) => e,
+            e => Error::Include(include.clone(), Box::new(e)),
+        })?;
+    }
+    stack.pop();
+    Ok(())
+}
+
+/// The config and the existing configs it includes, recursively, as far as they load, e.g. to watch them
+pub fn files(path: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = Vec::new();
+    let mut seen: Vec<PathBuf> = Vec::new();
+    let mut pending = vec![path.to_path_buf()];
+    while let Some(path) = pending.pop() {
+        let identity = path.canonicalize().unwrap_or_else(|_| path.clone());
+        if seen.contains(&identity) {
+            continue;
+        }
+        seen.push(identity);
+        if let Ok(config) = format::load(&path) {
+            let parent = path.parent().unwrap_or(Path::new(""));
+            // a missing include is reported by the build
+            let includes = config
+                .include
+                .iter()
+                .rev()
+                .map(|include| parent.join(include));
+            pending.extend(includes.filter(|include| include.exists()));
+        }
+        files.push(path);
+    }
+    files
+}
+
+/// Load a config with everything it includes merged in
+pub fn load(path: &Path) -> Result<Config, Error> {
+    merge(load_parts(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Room;
+    use std::fs;
+
+    fn config(level: &str, rooms: &[(u64, &str)]) -> Config {
+        Config {
+            version: crate::migrate::VERSION,
+            include: Vec::new(),
+            locales: BTreeMap::new(),
+            levels: vec![Level {
+                level_name: level.into(),
+                player: None,
+                rooms: rooms
+                    .iter()
+                    .map(|(id, name)| Room {
+                        room_id: *id,
+                        room_name: name.to_string(),
+                        room_description: String::new(),
+                        room_type: "Plain".into(),
+                        biome_type: "Prarie".into(),
+                        objects: None,
+                        object_ids: Vec::new(),
+                        dir_obj_ids: Vec::new(),
+                        flags: Vec::new(),
+                        npcs: Vec::new(),
+                    })
+                    .collect(),
+            }],
+        }
+    }
+
+    #[test]
+    fn joins_levels_by_name() {
+        let merged = merge(vec![
+            ("a.yml".into(), config("test", &[(1, "plain")])),
+            ("b.yml".into(), config("test", &[(2, "barn")])),
+            ("c.yml".into(), config("other", &[(3, "forge")])),
+        ])
+        .unwrap();
+        assert_eq!(merged.levels.len(), 2);
+        assert_eq!(merged.levels[0].rooms.len(), 2);
+    }
+
+    #[test]
+    fn reports_conflicts_with_their_sources() {
+        let conflicts = merge(vec![
+            ("a.yml".into(), config("test", &[(1, "plain")])),
+            ("b.yml".into(), config("test", &[(1, "barn"), (2, "plain")])),
+        ]);
+        let Err(Error::Conflicts(conflicts)) = conflicts else {
+            panic!("expected conflicts");
+        };
+        let messages: Vec<String> = conflicts.iter().map(|c| c.to_string()).collect();
+        assert_eq!(
+            messages,
+            [
+                "room ID 1 is defined in both a.yml and b.yml",
+                "room `plain` is defined in both a.yml and b.yml",
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_duplicates_within_a_source() {
+        let conflicts = merge(vec![(
+            "a.yml".into(),
+            config("test", &[(1, "plain"), (1, "barn")]),
+        )]);
+        let Err(Error::Conflicts(conflicts)) = conflicts else {
+            panic!("expected conflicts");
+        };
+        let messages: Vec<String> = conflicts.iter().map(|c| c.to_string()).collect();
+        assert_eq!(messages, ["room ID 1 is defined twice in a.yml"]);
+    }
+
+    #[test]
+    fn follows_includes() {
+        let dir = std::env::temp_dir().join(format!("pray-merge-{}", std::process::id()));
+        fs::create_dir_all(dir.join("areas")).unwrap();
+
+        let mut main = config("test", &[(1, "plain")]);
+        main.include = vec!["areas/barn.json".into()];
+        format::save(&dir.join("config.yml"), &main).unwrap();
+        let mut barn = config("test", &[(2, "barn")]);
+        barn.include = vec!["../config.yml".into()];
+        format::save(&dir.join("areas/barn.json"), &barn).unwrap();
+
+        // barn includes the main config back
+        assert!(matches!(
+            load(&dir.join("config.yml")),
+            Err(Error::IncludeCycle(_))
+        ));
+
+        // the cycle is only followed once
+        assert_eq!(
+            files(&dir.join("config.yml")),
+            [dir.join("config.yml"), dir.join("areas/barn.json")]
+        );
+
+        barn.include.clear();
+        format::save(&dir.join("areas/barn.json"), &barn).unwrap();
+        let merged = load(&dir.join("config.yml")).unwrap();
+        let names: Vec<&str> = merged.levels[0]
+            .rooms
+            .iter()
+            .map(|room| room.room_name.as_str())
+            .collect();
+        assert_eq!(names, ["plain", "barn"]);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
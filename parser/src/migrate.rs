@@ -0,0 +1,218 @@
+//! Upgrades of config documents written for older versions of the config shape.
+//! Documents are migrated as loose values, before being read into a `Config`.
+
+use crate::format::Error;
+use serde_json::Value;
+
+/// Version of the config shape written by this pray_engine
+pub const VERSION: u64 = 2;
+
+/// Upgrades a document from version `from` to `from + 1`
+pub struct Migration {
+    pub from: u64,
+    pub description: &'static str,
+    /// Applies the migration, returning a line for each change it made
+    apply: fn(&mut Value) -> Vec<String>,
+}
+
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        from: 0,
+        description: "`affectsAction` is the ID of the affected action instead of an effect",
+        apply: affects_action_id,
+    },
+    Migration {
+        from: 1,
+        description: "`affectsAction` is an `enable` effect",
+        apply: affects_action_effects,
+    },
+];
+
+/// Version of a document, configs written before versioning have none and are version 0
+pub fn version(value: &Value) -> u64 {
+    value.get("version").and_then(Value::as_u64).unwrap_or(0)
+}
+
+/// Upgrade `value` to the current version step by step, returning what changed
+pub fn migrate(value: &mut Value) -> Result<Vec<String>, Error> {
+    let mut changes = Vec::new();
+    let mut version = version(value);
+    if version > VERSION {
+        return Err(Error::Version(version));
+    }
+
+    while version < VERSION {
+        let migration = MIGRATIONS
+            .iter()
+            .find(|migration| migration.from == version)
+            .expect("missing migration");
+        for change in (migration.apply)(value) {
+            changes.push(format!("{}: {}", migration.description, change));
+        }
+        version += 1;
+        if let Value::Object(map) = value {
+            map.insert("version".to_string(), version.into());
+        }
+        changes.push(format!("version {} -> {}", version - 1, version));
+    }
+    Ok(changes)
+}
+
+fn name(value: &Value, key: &str) -> String {
+    value
+        .get(key)
+        .map(|name| name.to_string().trim_matches('"').to_string())
+        .unwrap_or_default()
+}
+
+/// The actions of `objects` and of the objects they contain, with a readable location
+fn object_actions<'a>(
+    place: &str,
+    objects: Option<&'a mut Value>,
+    actions: &mut Vec<(String, &'a mut Value)>,
+) {
+    let objects = objects.and_then(Value::as_array_mut);
+    for object in objects.into_iter().flatten() {
+        let place = format!("{}, object {}", place, name(object, "objID"));
+        let Some(object) = object.as_object_mut() else {
+            continue;
+        };
+        for (key, field) in object.iter_mut() {
+            match key.as_str() {
+                "actions" => {
+                    for action in field.as_array_mut().into_iter().flatten() {
+                        let location = format!("{}, action {}", place, name(action, "actionID"));
+                        actions.push((location, action));
+                    }
+                }
+                "contains" => object_actions(&place, Some(field), actions),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Every action of the document, in rooms, containers and inventories, with a readable location
+fn actions(value: &mut Value) -> Vec<(String, &mut Value)> {
+    let mut actions = Vec::new();
+    let levels = value.get_mut("levels").and_then(Value::as_array_mut);
+    for level in levels.into_iter().flatten() {
+        let level_name = name(level, "levelName");
+        let Some(level) = level.as_object_mut() else {
+            continue;
+        };
+        for (key, field) in level.iter_mut() {
+            match key.as_str() {
+                "rooms" => {
+                    for room in field.as_array_mut().into_iter().flatten() {
+                        let place =
+                            format!("level {}, room {}", level_name, name(room, "roomName"));
+                        object_actions(&place, room.get_mut("objects"), &mut actions);
+                    }
+                }
+                "player" => {
+                    let place = format!("level {}, inventory", level_name);
+                    object_actions(&place, field.get_mut("inventory"), &mut actions);
+                }
+                _ => {}
+            }
+        }
+    }
+    actions
+}
+
+/// 0 -> 1: `affectsAction: { actionID: 123, ... }` becomes `affectsAction: 123`
+fn affects_action_id(value: &mut Value) -> Vec<String> {
+    let mut changes = Vec::new();
+    for (location, action) in actions(value) {
+        let Some(affects) = action.get_mut("affectsAction") else {
+            continue;
+        };
+        if affects.is_object() {
+            *affects = affects.get("actionID").cloned().unwrap_or(Value::Null);
+            changes.push(location);
+        }
+    }
+    changes
+}
+
+/// 1 -> 2: `affectsAction: 123` becomes `effects: [{ kind: enable, action: 123 }]`
+fn affects_action_effects(value: &mut Value) -> Vec<String> {
+    let mut changes = Vec::new();
+    for (location, action) in actions(value) {
+        let Some(action) = action.as_object_mut() else {
+            continue;
+        };
+        match action.remove("affectsAction") {
+            None | Some(Value::Null) => {}
+            Some(affected) => {
+                let effects = serde_json::json!([{ "kind": "enable", "action": affected }]);
+                action.insert("effects".to_string(), effects);
+                changes.push(location);
+            }
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::Format;
+    use crate::Effect;
+
+    const UNVERSIONED: &str = "
+levels:
+- levelName: test
+  rooms:
+  - roomID: 1
+    roomName: room
+    roomDescription: a room
+    roomType: Plain
+    biomeType: Prarie
+    objectIds: [2]
+    dirObjIds: []
+    objects:
+    - objID: 2
+      type: Box
+      material: Wood
+      objDescription: a box
+      direction: null
+      destination: null
+      actions:
+      - actionID: 3
+        type: Open
+        enabled: true
+        revertable: false
+        dBitText: it opens
+        dBit: false
+        affectsAction:
+          actionID: 4
+          objectID: 2
+";
+
+    #[test]
+    fn migrates_unversioned_configs() {
+        let mut value: Value = Format::Yaml.deserialize(UNVERSIONED).unwrap();
+        let changes = migrate(&mut value).unwrap();
+        assert_eq!(changes.len(), 4);
+        assert_eq!(version(&value), VERSION);
+
+        let config = Format::Yaml.parse(UNVERSIONED).unwrap();
+        assert_eq!(config.version, VERSION);
+        let actions = config.levels[0].rooms[0].objects.as_ref().unwrap()[0]
+            .actions
+            .as_ref()
+            .unwrap();
+        assert_eq!(actions[0].effects, [Effect::Enable { action: 4 }]);
+
+        // migrating again changes nothing
+        assert!(migrate(&mut value).unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_newer_configs() {
+        let mut value = serde_json::json!({ "version": VERSION + 1, "levels": [] });
+        assert!(matches!(migrate(&mut value), Err(Error::Version(_))));
+    }
+}
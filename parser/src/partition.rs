@@ -0,0 +1,166 @@
+//! Splitting the set up of a world into batches small enough for a transaction,
+//! each to be generated as its own entrypoint or contract and called in order.
+
+use crate::stats::{self, Function};
+use crate::Config;
+use serde::{Deserialize, Serialize};
+
+/// How rooms are grouped into batches, a batch never spans levels
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+#[serde(tag = "by", rename_all = "camelCase")]
+pub enum Partition {
+    /// A batch per level
+    Level,
+    /// Batches of at most `rooms` rooms
+    Rooms { rooms: usize },
+    /// Batches within an estimated cost, see `stats`; a room over it is a batch on its own
+    Cost {
+        max_writes: Option<usize>,
+        max_felts: Option<usize>,
+    },
+}
+
+/// Rooms set up together
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Batch {
+    /// `batch_<index>`, batches are numbered in call order
+    pub name: String,
+    pub level_name: String,
+    #[serde(rename = "roomIDs")]
+    pub room_ids: Vec<u64>,
+    /// Estimated storage writes of the batch
+    pub writes: usize,
+    /// Estimated felts of text of the batch
+    pub felts: usize,
+}
+
+impl Batch {
+    /// The spawner entrypoint setting the batch up
+    pub fn entrypoint(&self) -> String {
+        format!("setup_{}", self.name)
+    }
+}
+
+impl Partition {
+    /// Whether `room` still fits in `batch`
+    fn fits(&self, batch: &Batch, room: &Function) -> bool {
+        let within = |value: usize, max: Option<usize>| max.is_none_or(|max| value <= max);
+        match self {
+            Partition::Level => true,
+            Partition::Rooms { rooms } => batch.room_ids.len() < *rooms,
+            Partition::Cost {
+                max_writes,
+                max_felts,
+            } => {
+                within(batch.writes + room.writes, *max_writes)
+                    && within(batch.felts + room.felts, *max_felts)
+            }
+        }
+    }
+}
+
+/// The batches of the rooms of every level, in the order they are to be called
+pub fn partition(config: &Config, by: &Partition) -> Vec<Batch> {
+    let mut batches: Vec<Batch> = Vec::new();
+    for level in &config.levels {
+        let mut current: Option<Batch> = None;
+        for room in &level.rooms {
+            let function = stats::room(room);
+            if let Some(batch) = current.as_mut().filter(|batch| by.fits(batch, &function)) {
+                batch.room_ids.push(room.room_id);
+                batch.writes += function.writes;
+                batch.felts += function.felts;
+                continue;
+            }
+            let next = Batch {
+                name: format!("batch_{}", batches.len() + usize::from(current.is_some())),
+                level_name: level.level_name.clone(),
+                room_ids: vec![room.room_id],
+                writes: function.writes,
+                felts: function.felts,
+            };
+            batches.extend(current.replace(next));
+        }
+        batches.extend(current);
+    }
+    batches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Level, Room};
+
+    fn config(levels: &[(&str, &[u64])]) -> Config {
+        let room = |room_id| Room {
+            room_id,
+            room_name: format!("room {}", room_id),
+            room_description: String::new(),
+            room_type: "Plain".into(),
+            biome_type: "Prarie".into(),
+            objects: None,
+            object_ids: Vec::new(),
+            dir_obj_ids: Vec::new(),
+            flags: Vec::new(),
+            npcs: Vec::new(),
+        };
+        Config {
+            version: crate::migrate::VERSION,
+            include: Vec::new(),
+            locales: Default::default(),
+            levels: levels
+                .iter()
+                .map(|(name, rooms)| Level {
+                    level_name: name.to_string(),
+                    player: None,
+                    rooms: rooms.iter().copied().map(room).collect(),
+                })
+                .collect(),
+        }
+    }
+
+    fn rooms(batches: &[Batch]) -> Vec<(&str, &[u64])> {
+        batches
+            .iter()
+            .map(|batch| (batch.name.as_str(), batch.room_ids.as_slice()))
+            .collect()
+    }
+
+    #[test]
+    fn groups_rooms_within_levels() {
+        let config = config(&[("one", &[1, 2, 3]), ("two", &[4])]);
+
+        let batches = partition(&config, &Partition::Level);
+        assert_eq!(
+            rooms(&batches),
+            [("batch_0", &[1, 2, 3][..]), ("batch_1", &[4])]
+        );
+
+        let batches = partition(&config, &Partition::Rooms { rooms: 2 });
+        assert_eq!(
+            rooms(&batches),
+            [
+                ("batch_0", &[1, 2][..]),
+                ("batch_1", &[3]),
+                ("batch_2", &[4])
+            ]
+        );
+
+        // every room without objects makes 2 writes
+        let by = Partition::Cost {
+            max_writes: Some(5),
+            max_felts: None,
+        };
+        let batches = partition(&config, &by);
+        assert_eq!(
+            rooms(&batches),
+            [
+                ("batch_0", &[1, 2][..]),
+                ("batch_1", &[3]),
+                ("batch_2", &[4])
+            ]
+        );
+        assert_eq!(batches[0].writes, 4);
+    }
+}
@@ -0,0 +1,133 @@
+//! JSON Schemas for the config and the markdown YAML blocks, so editors can autocomplete
+//! and validate them. The known values are the ones the spawner template maps to the game enums.
+
+use crate::fences::{ActionYaml, NpcYaml, ObjectYaml, ReactionYaml, RoomYaml};
+use crate::Config;
+use schemars::{json_schema, schema_for, Schema, SchemaGenerator};
+
+pub const ROOM_TYPES: &[&str] = &[
+    "WoodCabin",
+    "Store",
+    "Cavern",
+    "StoneCabin",
+    "Fort",
+    "Room",
+    "Plain",
+    "Mountains",
+    "Barn",
+    "Forge",
+    "Pass",
+    "Alley",
+];
+
+pub const OBJECT_TYPES: &[&str] = &[
+    "Path", "Window", "Ball", "Door", "Stairs", "Place", "Troll", "Chest", "Box", "Matches", "Can",
+    "Dynamite", "Boulder", "Bale", "Petrol",
+];
+
+pub const ACTION_TYPES: &[&str] = &[
+    "Move",
+    "Look",
+    "Kick",
+    "Hit",
+    "Drink",
+    "Fight",
+    "Sleep",
+    "Smash",
+    "Pray",
+    "Open",
+    "Break",
+    "Burn",
+    "Light",
+    "Spawn",
+    "Take",
+    "Help",
+    "Pour",
+    "Follow",
+    "Jump",
+    "Block",
+    "Soak",
+    "Empty",
+    "Explode",
+    "Disintegrate",
+    "Close",
+];
+
+pub const MATERIALS: &[&str] = &[
+    "Wood", "Dirt", "Stone", "Flesh", "Glass", "IKEA", "Iron", "Shit", "Mud", "Leather", "Metal",
+    "TNT", "Hay",
+];
+
+/// Directions as stored in the config
+pub const DIRECTIONS: &[&str] = &["N", "E", "S", "W", "U", "D"];
+
+/// Directions as written in the markdown
+pub const DIRECTION_NAMES: &[&str] = &["North", "East", "South", "West", "Up", "Down"];
+
+fn one_of(values: &[&str]) -> Schema {
+    json_schema!({ "type": "string", "enum": values })
+}
+
+fn one_of_or_null(values: &[&str]) -> Schema {
+    let mut values: Vec<Option<&str>> = values.iter().copied().map(Some).collect();
+    values.push(None);
+    json_schema!({ "type": ["string", "null"], "enum": values })
+}
+
+pub fn room_type(_: &mut SchemaGenerator) -> Schema {
+    one_of(ROOM_TYPES)
+}
+
+pub fn object_type(_: &mut SchemaGenerator) -> Schema {
+    one_of(OBJECT_TYPES)
+}
+
+pub fn action_type(_: &mut SchemaGenerator) -> Schema {
+    one_of(ACTION_TYPES)
+}
+
+pub fn material(_: &mut SchemaGenerator) -> Schema {
+    one_of(MATERIALS)
+}
+
+pub fn direction(_: &mut SchemaGenerator) -> Schema {
+    one_of_or_null(DIRECTIONS)
+}
+
+pub fn direction_name(_: &mut SchemaGenerator) -> Schema {
+    one_of_or_null(DIRECTION_NAMES)
+}
+
+/// IDs are written as integers, or as strings of digits where integers stop at i64::MAX (TOML)
+fn id_schema() -> Schema {
+    json_schema!({
+        "anyOf": [
+            { "type": "integer", "minimum": 0 },
+            { "type": "string", "pattern": "^[0-9]+$" }
+        ]
+    })
+}
+
+pub fn id(_: &mut SchemaGenerator) -> Schema {
+    id_schema()
+}
+
+pub fn id_or_null(_: &mut SchemaGenerator) -> Schema {
+    json_schema!({ "anyOf": [id_schema(), { "type": "null" }] })
+}
+
+pub fn ids(_: &mut SchemaGenerator) -> Schema {
+    json_schema!({ "type": "array", "items": id_schema() })
+}
+
+/// Every schema with the file name it is usually exported as
+pub fn schemas() -> Vec<(&'static str, Schema)> {
+    vec![
+        ("config.schema.json", schema_for!(Config)),
+        ("room.schema.json", schema_for!(RoomYaml)),
+        ("object.schema.json", schema_for!(ObjectYaml)),
+        ("action.schema.json", schema_for!(ActionYaml)),
+        ("npc.schema.json", schema_for!(NpcYaml)),
+        ("reaction.schema.json", schema_for!(ReactionYaml)),
+    ]
+}
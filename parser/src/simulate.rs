@@ -0,0 +1,456 @@
+//! Playing a level without the game, to find out which actions can be performed.
+//! Performing an action marks it fired and applies its effects, `Take` puts a
+//! takeable object in the inventory and `Move` or `Open` on an exit leads to its destination.
+
+use crate::{Action, Condition, Effect, Level, Location, Object, Placed};
+use std::collections::{BTreeSet, HashMap};
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+/// `roomID` of the room an exit leads to, rooms are identified by their markdown file
+pub fn room_id(destination: &str) -> u64 {
+    let mut s = DefaultHasher::new();
+    destination.hash(&mut s);
+    s.finish()
+}
+
+/// Why an action can not be performed
+#[derive(Debug, PartialEq)]
+pub enum Blocked {
+    /// No action has this `actionID`
+    Unknown,
+    /// Its object is neither carried nor in the room the player is in
+    Elsewhere,
+    Disabled,
+    /// The conditions that do not hold
+    Unmet(Vec<Condition>),
+}
+
+#[derive(Debug, Clone)]
+pub struct State<'a> {
+    level: &'a Level,
+    /// Where each object is, by `objID`
+    locations: HashMap<u64, Location>,
+    /// `objID`s of the objects that can be picked up
+    takeable: BTreeSet<u64>,
+    /// Every action with its object, in the order of `Level::objects`
+    actions: Vec<(&'a Action, &'a Object)>,
+    /// Effects only add, never take away, see `reachable`
+    optimistic: bool,
+    /// `roomID` of the room the player is in
+    pub room: u64,
+    /// `objID`s of the objects carried
+    pub inventory: BTreeSet<u64>,
+    /// `objID`s of the objects out of the world, hidden or destroyed
+    pub gone: BTreeSet<u64>,
+    /// `actionID`s of the actions performed
+    pub fired: BTreeSet<u64>,
+    /// `actionID`s of the actions that can be performed
+    pub enabled: BTreeSet<u64>,
+    /// Raised flags with the `roomID` of their room
+    pub flags: BTreeSet<(u64, String)>,
+}
+
+impl<'a> State<'a> {
+    /// The level as it starts, `None` when it has no rooms
+    pub fn new(level: &'a Level) -> Option<State<'a>> {
+        let room = level.start_room()?.room_id;
+        let mut locations = HashMap::new();
+        let mut takeable = BTreeSet::new();
+        let mut actions = Vec::new();
+        let mut inventory = BTreeSet::new();
+        let mut gone = BTreeSet::new();
+        let mut enabled = BTreeSet::new();
+        for Placed { location, object } in level.objects() {
+            locations.insert(object.obj_id, location);
+            if object.takeable {
+                takeable.insert(object.obj_id);
+            }
+            if object.hidden {
+                gone.insert(object.obj_id);
+            }
+            if location == Location::Inventory {
+                inventory.insert(object.obj_id);
+            }
+            for action in object.actions.iter().flatten() {
+                actions.push((action, object));
+                if action.enabled {
+                    enabled.insert(action.action_id);
+                }
+            }
+        }
+        let flags = level
+            .rooms
+            .iter()
+            .flat_map(|room| room.flags.iter().map(|flag| (room.room_id, flag.clone())))
+            .collect();
+
+        Some(State {
+            level,
+            locations,
+            takeable,
+            actions,
+            optimistic: false,
+            room,
+            inventory,
+            gone,
+            fired: BTreeSet::new(),
+            enabled,
+            flags,
+        })
+    }
+
+    /// Whether the object is carried, or in the room the player is in
+    pub fn is_here(&self, obj_id: u64) -> bool {
+        if self.gone.contains(&obj_id) {
+            return false;
+        }
+        if self.inventory.contains(&obj_id) {
+            return true;
+        }
+        match self.locations.get(&obj_id) {
+            Some(Location::Room(id)) => *id == self.room,
+            Some(Location::Container(id)) => self.is_here(*id),
+            Some(Location::Inventory) | None => false,
+        }
+    }
+
+    pub fn holds(&self, condition: &Condition) -> bool {
+        match condition {
+            Condition::Holding { holding } => self.inventory.contains(holding),
+            Condition::Fired { fired } => self.fired.contains(fired),
+            Condition::Flag { flag } => self.flags.contains(&(self.room, flag.clone())),
+        }
+    }
+
+    fn find(&self, action_id: u64) -> Option<(&'a Action, &'a Object)> {
+        self.actions
+            .iter()
+            .find(|(action, _)| action.action_id == action_id)
+            .copied()
+    }
+
+    /// The conditions of the action that do not hold
+    fn unmet(&self, action: &Action) -> Vec<Condition> {
+        action
+            .requires
+            .iter()
+            .filter(|condition| !self.holds(condition))
+            .cloned()
+            .collect()
+    }
+
+    /// The action with its object when it can be performed now
+    pub fn check(&self, action_id: u64) -> Result<(&'a Action, &'a Object), Blocked> {
+        let Some((action, object)) = self.find(action_id) else {
+            return Err(Blocked::Unknown);
+        };
+        if !self.is_here(object.obj_id) {
+            return Err(Blocked::Elsewhere);
+        }
+        if !self.enabled.contains(&action_id) {
+            return Err(Blocked::Disabled);
+        }
+        let unmet = self.unmet(action);
+        if !unmet.is_empty() {
+            return Err(Blocked::Unmet(unmet));
+        }
+        Ok((action, object))
+    }
+
+    /// The actions that can be performed now
+    pub fn available(&self) -> Vec<&'a Action> {
+        self.actions
+            .iter()
+            .map(|(action, _)| *action)
+            .filter(|action| self.check(action.action_id).is_ok())
+            .collect()
+    }
+
+    /// Pick up a takeable object in the room, false when it can not be
+    pub fn take(&mut self, obj_id: u64) -> bool {
+        if !self.takeable.contains(&obj_id) || !self.is_here(obj_id) {
+            return false;
+        }
+        self.inventory.insert(obj_id);
+        true
+    }
+
+    pub fn perform(&mut self, action_id: u64) -> Result<(), Blocked> {
+        let (action, object) = self.check(action_id)?;
+        self.fire(action, object, &mut Vec::new());
+        Ok(())
+    }
+
+    /// Apply what performing the action does, `triggered` holds the actions being performed
+    /// so actions triggering each other stop
+    fn fire(&mut self, action: &Action, object: &Object, triggered: &mut Vec<u64>) {
+        triggered.push(action.action_id);
+        self.fired.insert(action.action_id);
+        if action.ttype == "Take" && object.takeable {
+            self.inventory.insert(object.obj_id);
+        }
+        if let (Some(destination), "Move" | "Open") = (&object.destination, action.ttype.as_str()) {
+            self.enter(room_id(destination));
+        }
+        for effect in &action.effects {
+            self.apply(effect, triggered);
+        }
+    }
+
+    fn enter(&mut self, room_id: u64) {
+        if self.level.rooms.iter().any(|room| room.room_id == room_id) {
+            self.room = room_id;
+        }
+    }
+
+    fn apply(&mut self, effect: &Effect, triggered: &mut Vec<u64>) {
+        // what an effect takes away, when effects only add
+        let keep = self.optimistic;
+        match effect {
+            Effect::Enable { action } => {
+                self.enabled.insert(*action);
+            }
+            Effect::Disable { action } => {
+                if !keep {
+                    self.enabled.remove(action);
+                }
+            }
+            Effect::Toggle { action } => {
+                if !self.enabled.insert(*action) && !keep {
+                    self.enabled.remove(action);
+                }
+            }
+            Effect::Trigger { action } => {
+                let Some((action, object)) = self.find(*action) else {
+                    return;
+                };
+                if triggered.contains(&action.action_id)
+                    || !self.enabled.contains(&action.action_id)
+                    || !self.unmet(action).is_empty()
+                {
+                    return;
+                }
+                self.fire(action, object, triggered);
+            }
+            Effect::Move { room } => self.enter(*room),
+            Effect::Spawn { object, room } => {
+                self.gone.remove(object);
+                let room = room.unwrap_or(self.room);
+                self.locations.insert(*object, Location::Room(room));
+            }
+            Effect::Destroy { object } => {
+                if !keep {
+                    self.gone.insert(*object);
+                    self.inventory.remove(object);
+                }
+            }
+            Effect::Raise { flag, room } => {
+                let room = room.unwrap_or(self.room);
+                self.flags.insert((room, flag.clone()));
+            }
+            Effect::Lower { flag, room } => {
+                if !keep {
+                    let room = room.unwrap_or(self.room);
+                    self.flags.remove(&(room, flag.clone()));
+                }
+            }
+        }
+    }
+}
+
+/// What some playthrough of a level gets to
+#[derive(Debug, Default, PartialEq)]
+pub struct Reachable {
+    /// `roomID`s of the rooms entered
+    pub rooms: BTreeSet<u64>,
+    /// `actionID`s of the actions performed
+    pub actions: BTreeSet<u64>,
+}
+
+/// Everything that can be reached by playing the level, in any order.
+/// Effects taking things away are ignored, so performing an action never undoes another
+/// one and doing everything possible in every room entered, until nothing changes, reaches
+/// all of it, and perhaps more.
+pub fn reachable(level: &Level) -> Reachable {
+    let Some(mut state) = State::new(level) else {
+        return Reachable::default();
+    };
+    state.optimistic = true;
+    let takeable = state.takeable.clone();
+    let mut rooms = BTreeSet::from([state.room]);
+    let progress = |rooms: &BTreeSet<u64>, state: &State| {
+        (
+            rooms.len(),
+            state.fired.len(),
+            state.inventory.len(),
+            state.enabled.len(),
+            state.gone.len(),
+            state.flags.len(),
+        )
+    };
+    loop {
+        let before = progress(&rooms, &state);
+        for room in rooms.clone() {
+            state.room = room;
+            for obj_id in &takeable {
+                state.take(*obj_id);
+            }
+            for action in state.available() {
+                state.room = room;
+                state
+                    .perform(action.action_id)
+                    .expect("available action can be performed");
+                rooms.insert(state.room);
+            }
+        }
+        if progress(&rooms, &state) == before {
+            break;
+        }
+    }
+
+    Reachable {
+        rooms,
+        actions: state.fired,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Room;
+
+    fn object(obj_id: u64, ttype: &str, actions: Vec<Action>) -> Object {
+        Object {
+            obj_id,
+            ttype: ttype.into(),
+            material: "Wood".into(),
+            obj_description: String::new(),
+            direction: None,
+            destination: None,
+            actions: Some(actions),
+            takeable: false,
+            hidden: false,
+            contains: Vec::new(),
+        }
+    }
+
+    fn action(action_id: u64, ttype: &str, requires: Vec<Condition>) -> Action {
+        Action {
+            action_id,
+            ttype: ttype.into(),
+            enabled: true,
+            revertable: false,
+            d_bit_text: String::new(),
+            d_bit: true,
+            effects: Vec::new(),
+            requires,
+        }
+    }
+
+    /// Matches in the plain, a bale in the barn that burns once soaked with petrol
+    fn level() -> Level {
+        let mut matches = object(1, "Matches", vec![]);
+        matches.takeable = true;
+        let mut door = object(2, "Door", vec![action(20, "Open", vec![])]);
+        door.destination = Some("barn.md".into());
+        let bale = object(
+            3,
+            "Bale",
+            vec![
+                action(
+                    30,
+                    "Soak",
+                    vec![Condition::Flag {
+                        flag: "petrol".into(),
+                    }],
+                ),
+                action(
+                    31,
+                    "Burn",
+                    vec![
+                        Condition::Holding { holding: 1 },
+                        Condition::Fired { fired: 30 },
+                    ],
+                ),
+            ],
+        );
+        let room = |room_id, objects| Room {
+            room_id,
+            room_name: String::new(),
+            room_description: String::new(),
+            room_type: "Barn".into(),
+            biome_type: "Prarie".into(),
+            objects: Some(objects),
+            object_ids: Vec::new(),
+            dir_obj_ids: Vec::new(),
+            flags: Vec::new(),
+            npcs: Vec::new(),
+        };
+        let mut barn = room(room_id("barn.md"), vec![bale]);
+        barn.flags = vec!["petrol".into()];
+        Level {
+            level_name: "test".into(),
+            player: None,
+            rooms: vec![room(room_id("plain.md"), vec![matches, door]), barn],
+        }
+    }
+
+    #[test]
+    fn performs_actions_once_their_conditions_hold() {
+        let level = level();
+        let mut state = State::new(&level).unwrap();
+        assert_eq!(state.perform(31), Err(Blocked::Elsewhere));
+        assert!(state.take(1));
+        state.perform(20).unwrap();
+        assert_eq!(state.room, room_id("barn.md"));
+        assert_eq!(
+            state.perform(31),
+            Err(Blocked::Unmet(vec![Condition::Fired { fired: 30 }]))
+        );
+        state.perform(30).unwrap();
+        state.perform(31).unwrap();
+    }
+
+    #[test]
+    fn finds_what_can_be_reached() {
+        let mut level = level();
+        assert_eq!(reachable(&level).actions, BTreeSet::from([20, 30, 31]));
+
+        // without petrol the bale is never soaked so never burns
+        level.rooms[1].flags.clear();
+        let reachable = reachable(&level);
+        assert_eq!(reachable.actions, BTreeSet::from([20]));
+        assert_eq!(reachable.rooms.len(), 2);
+    }
+
+    #[test]
+    fn applies_effects_in_order() {
+        let mut level = level();
+        let mut ash = object(4, "Place", vec![action(40, "Look", vec![])]);
+        ash.hidden = true;
+        let bale = &mut level.rooms[1].objects.as_mut().unwrap()[0];
+        let burn = &mut bale.actions.as_mut().unwrap()[1];
+        burn.requires.clear();
+        burn.effects = vec![
+            Effect::Destroy { object: 3 },
+            Effect::Spawn {
+                object: 4,
+                room: None,
+            },
+            Effect::Disable { action: 20 },
+            Effect::Trigger { action: 40 },
+        ];
+        level.rooms[1].objects.as_mut().unwrap().push(ash);
+
+        let mut state = State::new(&level).unwrap();
+        state.perform(20).unwrap();
+        assert_eq!(state.check(40), Err(Blocked::Elsewhere));
+        state.perform(31).unwrap();
+        assert!(!state.is_here(3));
+        assert!(state.fired.contains(&40));
+        assert!(!state.enabled.contains(&20));
+
+        // the door is disabled once the bale burns, but exploring ignores that
+        assert!(reachable(&level).actions.contains(&20));
+    }
+}
@@ -0,0 +1,336 @@
+//! Estimates of what a world costs on chain once generated by the spawner template,
+//! which sets each room up in its own function, writing every text as a `ByteArray`,
+//! and calls them all from `setup`, or from an entrypoint per batch when they are partitioned.
+
+use crate::partition::{self, Batch, Partition};
+use crate::{locale, Config, Level, Object, Room};
+use serde::{Deserialize, Serialize};
+
+/// Bytes held by a felt of a `ByteArray`
+pub const FELT_BYTES: usize = 31;
+
+/// How many longest texts are listed per level
+const LONGEST: usize = 5;
+
+/// Felts of a `ByteArray` holding `text`: the length of its words, its full words,
+/// the pending word and its length
+pub fn felts(text: &str) -> usize {
+    text.len() / FELT_BYTES + 3
+}
+
+/// A generated function
+#[derive(Serialize, Debug, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Function {
+    pub name: String,
+    /// Storage writes of texts, objects, actions and rooms
+    pub writes: usize,
+    /// Felts of the texts written, passed around as `ByteArray`s
+    pub felts: usize,
+    /// Rooms set up, for `setup` and the batch entrypoints
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rooms: Option<usize>,
+}
+
+impl Function {
+    /// Add the writes of an object: its text, itself, its actions and the objects inside it
+    fn object(&mut self, object: &Object) {
+        let actions = object.actions.as_deref().unwrap_or_default();
+        self.writes += 2 + actions.len();
+        self.felts += felts(&object.obj_description);
+        self.felts += object.destination.as_deref().map_or(0, felts);
+        for action in actions {
+            self.felts += felts(&action.d_bit_text);
+        }
+        for inner in &object.contains {
+            self.object(inner);
+        }
+    }
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Text {
+    /// See `locale::TextId`
+    pub id: String,
+    pub bytes: usize,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LevelStats {
+    pub level_name: String,
+    pub rooms: usize,
+    pub objects: usize,
+    pub actions: usize,
+    pub npcs: usize,
+    /// Bytes of every text of the level
+    pub text_bytes: usize,
+    /// The longest texts, longest first
+    pub longest: Vec<Text>,
+    /// A function per room, one for the inventory, and `setup` calling them all
+    /// or an entrypoint per batch
+    pub functions: Vec<Function>,
+}
+
+/// The function setting a room up
+pub fn room(room: &Room) -> Function {
+    // the description and the place, the name is hashed into the room ID
+    let mut function = Function {
+        name: format!("gen_room_{}", room.room_id),
+        writes: 2,
+        felts: felts(&room.room_description) + felts(&room.room_name),
+        ..Function::default()
+    };
+    for object in room.objects.iter().flatten() {
+        function.object(object);
+    }
+    function
+}
+
+fn level(level: &Level, batches: Option<&[Batch]>) -> LevelStats {
+    let placed = level.objects();
+    let texts = locale::level_texts(level);
+    let mut longest: Vec<Text> = texts
+        .iter()
+        .map(|(id, text)| Text {
+            id: id.clone(),
+            bytes: text.len(),
+        })
+        .collect();
+    longest.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.id.cmp(&b.id)));
+    longest.truncate(LONGEST);
+
+    let mut functions: Vec<Function> = level.rooms.iter().map(self::room).collect();
+    if let Some(player) = level.player.as_ref().filter(|p| !p.inventory.is_empty()) {
+        let mut function = Function {
+            name: "make_inventory".into(),
+            ..Function::default()
+        };
+        for object in &player.inventory {
+            function.object(object);
+        }
+        functions.push(function);
+    }
+    match batches {
+        None => {
+            let setup = Function {
+                name: "setup".into(),
+                writes: functions.iter().map(|f| f.writes).sum(),
+                felts: functions.iter().map(|f| f.felts).sum(),
+                rooms: Some(level.rooms.len()),
+            };
+            functions.push(setup);
+        }
+        Some(batches) => functions.extend(
+            batches
+                .iter()
+                .filter(|batch| batch.level_name == level.level_name)
+                .map(|batch| Function {
+                    name: batch.entrypoint(),
+                    writes: batch.writes,
+                    felts: batch.felts,
+                    rooms: Some(batch.room_ids.len()),
+                }),
+        ),
+    }
+
+    LevelStats {
+        level_name: level.level_name.clone(),
+        rooms: level.rooms.len(),
+        objects: placed.len(),
+        actions: placed
+            .iter()
+            .map(|p| p.object.actions.as_ref().map_or(0, Vec::len))
+            .sum(),
+        npcs: level.rooms.iter().map(|room| room.npcs.len()).sum(),
+        text_bytes: texts.values().map(String::len).sum(),
+        longest,
+        functions,
+    }
+}
+
+/// The stats of every level, with its rooms set up batch by batch when they are partitioned
+pub fn stats(config: &Config, by: Option<&Partition>) -> Vec<LevelStats> {
+    let batches = by.map(|by| partition::partition(config, by));
+    config
+        .levels
+        .iter()
+        .map(|l| level(l, batches.as_deref()))
+        .collect()
+}
+
+/// Limits a world has to stay within, unset ones are not checked
+#[derive(Deserialize, Serialize, Debug, PartialEq, Default, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Budget {
+    /// Bytes of a single text, only the longest texts of each level are reported
+    pub max_text_bytes: Option<usize>,
+    /// Storage writes of a generated function, `setup` or the batch entrypoints included
+    pub max_writes: Option<usize>,
+    /// Felts of the texts of a generated function, `setup` or the batch entrypoints included
+    pub max_felts: Option<usize>,
+    /// Rooms set up by `setup`, or by each batch entrypoint
+    pub max_rooms: Option<usize>,
+}
+
+impl Budget {
+    /// What goes over the budget, one line each
+    pub fn exceeded(&self, stats: &[LevelStats]) -> Vec<String> {
+        let mut exceeded = Vec::new();
+        let over = |value: usize, max: Option<usize>| max.is_some_and(|max| value > max);
+        for level in stats {
+            let name = &level.level_name;
+            for text in &level.longest {
+                if over(text.bytes, self.max_text_bytes) {
+                    exceeded.push(format!(
+                        "level {}, {} is {} bytes, the budget is {}",
+                        name,
+                        text.id,
+                        text.bytes,
+                        self.max_text_bytes.unwrap_or_default()
+                    ));
+                }
+            }
+            for function in &level.functions {
+                if let Some(rooms) = function.rooms.filter(|&rooms| over(rooms, self.max_rooms)) {
+                    exceeded.push(format!(
+                        "level {}, {} sets up {} rooms, the budget is {}",
+                        name,
+                        function.name,
+                        rooms,
+                        self.max_rooms.unwrap_or_default()
+                    ));
+                }
+                if over(function.writes, self.max_writes) {
+                    exceeded.push(format!(
+                        "level {}, {} makes {} storage writes, the budget is {}",
+                        name,
+                        function.name,
+                        function.writes,
+                        self.max_writes.unwrap_or_default()
+                    ));
+                }
+                if over(function.felts, self.max_felts) {
+                    exceeded.push(format!(
+                        "level {}, {} writes {} felts of text, the budget is {}",
+                        name,
+                        function.name,
+                        function.felts,
+                        self.max_felts.unwrap_or_default()
+                    ));
+                }
+            }
+        }
+        exceeded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn estimates_functions() {
+        let config = crate::parse(
+            r#"
+levels:
+- levelName: test
+  rooms:
+  - roomID: 1
+    roomName: plain
+    roomDescription: a plain that goes on and on and on, as far as the eye can see
+    roomType: Plain
+    biomeType: Prarie
+    objectIds: [2]
+    dirObjIds: []
+    objects:
+    - objID: 2
+      type: Bale
+      material: Hay
+      objDescription: a bale
+      direction: null
+      destination: null
+      actions:
+      - actionID: 3
+        type: Burn
+        enabled: true
+        revertable: false
+        dBitText: it burns
+        dBit: true
+"#,
+        );
+        let stats = stats(&config, None);
+        let level = &stats[0];
+        assert_eq!((level.rooms, level.objects, level.actions), (1, 1, 1));
+        assert_eq!(level.longest[0].id, "room.1.description");
+        assert_eq!(
+            level.functions[0],
+            Function {
+                name: "gen_room_1".into(),
+                writes: 5,
+                felts: 4 + 3 + 3 + 3,
+                rooms: None,
+            }
+        );
+        assert_eq!(level.functions[1].name, "setup");
+        assert_eq!(level.functions[1].rooms, Some(1));
+
+        let budget = Budget {
+            max_writes: Some(4),
+            ..Budget::default()
+        };
+        assert_eq!(
+            budget.exceeded(&stats),
+            [
+                "level test, gen_room_1 makes 5 storage writes, the budget is 4",
+                "level test, setup makes 5 storage writes, the budget is 4",
+            ]
+        );
+    }
+
+    #[test]
+    fn estimates_batches_instead_of_setup() {
+        let config = crate::parse(
+            r#"
+levels:
+- levelName: test
+  rooms:
+  - roomID: 1
+    roomName: a
+    roomDescription: the first room
+    roomType: Plain
+    biomeType: Prarie
+    objectIds: []
+    dirObjIds: []
+  - roomID: 2
+    roomName: b
+    roomDescription: the second room
+    roomType: Plain
+    biomeType: Prarie
+    objectIds: []
+    dirObjIds: []
+"#,
+        );
+        let budget = Budget {
+            max_writes: Some(2),
+            max_rooms: Some(1),
+            ..Budget::default()
+        };
+        assert_eq!(
+            budget.exceeded(&stats(&config, None)),
+            [
+                "level test, setup sets up 2 rooms, the budget is 1",
+                "level test, setup makes 4 storage writes, the budget is 2",
+            ]
+        );
+
+        let stats = stats(&config, Some(&Partition::Rooms { rooms: 1 }));
+        let names: Vec<_> = stats[0].functions.iter().map(|f| &f.name).collect();
+        assert_eq!(
+            names,
+            ["gen_room_1", "gen_room_2", "setup_batch_0", "setup_batch_1"]
+        );
+        assert!(budget.exceeded(&stats).is_empty());
+    }
+}
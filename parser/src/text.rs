@@ -0,0 +1,80 @@
+//! One table of the distinct texts of a world, for templates storing every text once
+//! instead of next to each room, object and action using it.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+/// A distinct text and the text IDs using it, see `locale::TextId`
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Text {
+    /// ID the text is stored under, the same for the same text
+    #[serde(rename = "txtID")]
+    pub txt_id: u64,
+    pub text: String,
+    pub owners: Vec<String>,
+}
+
+#[derive(Serialize, Debug, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Table {
+    /// Ordered by the first text ID using them
+    pub texts: Vec<Text>,
+    /// `txtID` of the text of each text ID
+    #[serde(rename = "byTextID")]
+    pub by_text_id: BTreeMap<String, u64>,
+}
+
+impl Table {
+    /// Texts used more than once, stored once
+    pub fn duplicates(&self) -> usize {
+        self.by_text_id.len() - self.texts.len()
+    }
+}
+
+pub fn txt_id(text: &str) -> u64 {
+    let mut s = DefaultHasher::new();
+    text.hash(&mut s);
+    s.finish()
+}
+
+/// The distinct texts of `texts`, texts by text ID as given by `locale::texts` or `locale::table`
+pub fn table(texts: &BTreeMap<String, String>) -> Table {
+    let mut table = Table::default();
+    // index in `table.texts` by `txtID`
+    let mut indexes: BTreeMap<u64, usize> = BTreeMap::new();
+    for (id, text) in texts {
+        let txt_id = txt_id(text);
+        let index = *indexes.entry(txt_id).or_insert_with(|| {
+            table.texts.push(Text {
+                txt_id,
+                text: text.clone(),
+                owners: Vec::new(),
+            });
+            table.texts.len() - 1
+        });
+        table.texts[index].owners.push(id.clone());
+        table.by_text_id.insert(id.clone(), txt_id);
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stores_identical_texts_once() {
+        let texts = BTreeMap::from([
+            ("action.1.text".to_string(), "it burns".to_string()),
+            ("action.2.text".to_string(), "it rolls".to_string()),
+            ("action.3.text".to_string(), "it burns".to_string()),
+        ]);
+        let table = table(&texts);
+        assert_eq!(table.texts.len(), 2);
+        assert_eq!(table.duplicates(), 1);
+        assert_eq!(table.texts[0].owners, ["action.1.text", "action.3.text"]);
+        assert_eq!(table.by_text_id["action.3.text"], txt_id("it burns"));
+    }
+}
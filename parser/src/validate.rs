@@ -0,0 +1,502 @@
+//! Mistakes a config can parse with: references to rooms, objects and actions that do not exist,
+//! conditions that can never hold, and rooms or actions a player can never get to.
+
+use crate::schema::ACTION_TYPES;
+use crate::simulate::{self, Reachable};
+use crate::{Condition, Config, Effect, Level, Location, Object, Placed};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub enum Severity {
+    /// The world is broken
+    Error,
+    /// Probably not what was meant
+    Warning,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Problem {
+    pub severity: Severity,
+    /// Where the problem is, e.g. "level test, object 12 `a dusty window`, action 3 Break"
+    pub at: String,
+    pub message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "{}: {}: {}", severity, self.at, self.message)
+    }
+}
+
+/// Every problem of the config, errors first
+pub fn validate(config: &Config) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    for level in &config.levels {
+        self::level(level, &mut problems);
+    }
+    problems.sort_by_key(|problem| problem.severity);
+    problems
+}
+
+/// What effects can point at
+struct Known<'a> {
+    rooms: &'a BTreeSet<u64>,
+    actions: &'a BTreeSet<u64>,
+    objects: &'a HashMap<u64, bool>,
+}
+
+impl Known<'_> {
+    /// Why the effect is broken, if it is
+    fn effect(&self, effect: &Effect) -> Option<String> {
+        match effect {
+            Effect::Move { room }
+            | Effect::Raise {
+                room: Some(room), ..
+            }
+            | Effect::Lower {
+                room: Some(room), ..
+            } if !self.rooms.contains(room) => {
+                Some(format!("affects room {} which does not exist", room))
+            }
+            Effect::Spawn {
+                room: Some(room), ..
+            } if !self.rooms.contains(room) => {
+                Some(format!("spawns into room {} which does not exist", room))
+            }
+            Effect::Spawn { object, .. } | Effect::Destroy { object }
+                if !self.objects.contains_key(object) =>
+            {
+                Some(format!("affects object {} which does not exist", object))
+            }
+            effect => match effect.action() {
+                Some(action) if !self.actions.contains(&action) => {
+                    Some(format!("affects action {} which does not exist", action))
+                }
+                _ => None,
+            },
+        }
+    }
+}
+
+/// The links the spawner writes for the actions of an object, as `(action, enabled action)`:
+/// the on-chain action has a single `affectsActionId`, set from the first `enable` effect
+/// on an action of the same object
+fn spawner_links(object: &Object) -> Vec<(u64, u64)> {
+    let actions = object.actions.as_deref().unwrap_or_default();
+    actions
+        .iter()
+        .filter_map(|action| {
+            let enabled = action.effects.iter().find_map(|effect| match effect {
+                Effect::Enable { action } if actions.iter().any(|a| a.action_id == *action) => {
+                    Some(*action)
+                }
+                _ => None,
+            })?;
+            Some((action.action_id, enabled))
+        })
+        .collect()
+}
+
+/// First line of the description of an object, the heading in the room markdown
+fn heading(object: &Object) -> &str {
+    object.obj_description.lines().next().unwrap_or_default()
+}
+
+fn level(level: &Level, problems: &mut Vec<Problem>) {
+    let mut problem = |severity, at: String, message: String| {
+        problems.push(Problem {
+            severity,
+            at: format!("level {}{}", level.level_name, at),
+            message,
+        })
+    };
+
+    if let Some(id) = level.player.as_ref().and_then(|player| player.start_room) {
+        if !level.rooms.iter().any(|room| room.room_id == id) {
+            let message = format!("starts in room {} which does not exist", id);
+            problem(Severity::Error, ", player".into(), message);
+        }
+    }
+
+    let placed = level.objects();
+    // IDs are keys of the on-chain models, a duplicate overwrites what it duplicates
+    let ids = level
+        .rooms
+        .iter()
+        .map(|room| ("room", room.room_id))
+        .chain(placed.iter().map(|placed| ("object", placed.object.obj_id)))
+        .chain(
+            placed
+                .iter()
+                .flat_map(|placed| placed.object.actions.iter().flatten())
+                .map(|action| ("action", action.action_id)),
+        )
+        .chain(
+            level
+                .rooms
+                .iter()
+                .flat_map(|room| &room.npcs)
+                .map(|npc| ("npc", npc.npc_id)),
+        );
+    let mut counts: BTreeMap<(&str, u64), usize> = BTreeMap::new();
+    for id in ids {
+        *counts.entry(id).or_default() += 1;
+    }
+    for ((what, id), count) in counts {
+        if count > 1 {
+            let message = format!("{} ID {} is used {} times", what, id, count);
+            problem(Severity::Error, String::new(), message);
+        }
+    }
+
+    // which objects could ever end up in the inventory
+    let holdable: HashMap<u64, bool> = placed
+        .iter()
+        .map(|Placed { location, object }| {
+            (
+                object.obj_id,
+                object.takeable || *location == Location::Inventory,
+            )
+        })
+        .collect();
+    let actions: BTreeSet<u64> = placed
+        .iter()
+        .flat_map(|placed| placed.object.actions.iter().flatten())
+        .map(|action| action.action_id)
+        .collect();
+    let rooms: BTreeSet<u64> = level.rooms.iter().map(|room| room.room_id).collect();
+    let known = Known {
+        rooms: &rooms,
+        actions: &actions,
+        objects: &holdable,
+    };
+    // flags raised by rooms when the level starts or by effects
+    let mut flags: BTreeSet<&str> = level
+        .rooms
+        .iter()
+        .flat_map(|room| room.flags.iter().map(String::as_str))
+        .collect();
+    let mut spawned = BTreeSet::new();
+    let reactions = level
+        .rooms
+        .iter()
+        .flat_map(|room| &room.npcs)
+        .flat_map(|npc| npc.reactions.values());
+    let effects = placed
+        .iter()
+        .flat_map(|placed| placed.object.actions.iter().flatten())
+        .flat_map(|action| &action.effects)
+        .chain(reactions.flat_map(|reaction| &reaction.effects));
+    for effect in effects {
+        match effect {
+            Effect::Raise { flag, .. } => {
+                flags.insert(flag);
+            }
+            Effect::Spawn { object, .. } => {
+                spawned.insert(*object);
+            }
+            _ => {}
+        }
+    }
+
+    let Reachable {
+        rooms: entered,
+        actions: performed,
+    } = simulate::reachable(level);
+    for room in &level.rooms {
+        if !entered.contains(&room.room_id) {
+            let at = format!(", room {}", room.room_name);
+            problem(Severity::Warning, at, "can never be entered".into());
+        }
+    }
+
+    for room in &level.rooms {
+        for npc in &room.npcs {
+            let at = format!(
+                ", room {}, npc {} `{}`",
+                room.room_name, npc.npc_id, npc.name
+            );
+            let mut error = |message: String| problem(Severity::Error, at.clone(), message);
+            for id in &npc.patrol {
+                if !rooms.contains(id) {
+                    error(format!("patrols room {} which does not exist", id));
+                }
+            }
+            for next in npc.dialogue.iter().flat_map(|line| &line.responses) {
+                let Some(next) = next.next else {
+                    continue;
+                };
+                if !npc.dialogue.iter().any(|line| line.line_id == next) {
+                    error(format!("answers with line {} which does not exist", next));
+                }
+            }
+            for effect in npc
+                .reactions
+                .values()
+                .flat_map(|reaction| &reaction.effects)
+            {
+                if let Some(message) = known.effect(effect) {
+                    error(message);
+                }
+            }
+            for ttype in npc.reactions.keys() {
+                if !ACTION_TYPES.contains(&ttype.as_str()) {
+                    let message = format!("reacts to `{}` which is not an action type", ttype);
+                    problem(Severity::Warning, at.clone(), message);
+                }
+            }
+        }
+    }
+
+    for Placed { object, .. } in &placed {
+        let links = spawner_links(object);
+        if object.hidden && !spawned.contains(&object.obj_id) {
+            let at = format!(", object {} `{}`", object.obj_id, heading(object));
+            problem(Severity::Warning, at, "is hidden and never spawned".into());
+        }
+        for action in object.actions.iter().flatten() {
+            let at = format!(
+                ", object {} `{}`, action {} {}",
+                object.obj_id,
+                heading(object),
+                action.action_id,
+                action.ttype
+            );
+            let mut broken = false;
+            let mut error = |message: String| {
+                broken = true;
+                problem(Severity::Error, at.clone(), message);
+            };
+
+            for effect in &action.effects {
+                if let Some(message) = known.effect(effect) {
+                    error(message);
+                }
+            }
+            for condition in &action.requires {
+                match condition {
+                    Condition::Holding { holding } => match holdable.get(holding) {
+                        None => error(format!("requires object {} which does not exist", holding)),
+                        Some(false) => error(format!(
+                            "requires holding object {} which is not takeable",
+                            holding
+                        )),
+                        Some(true) => {}
+                    },
+                    Condition::Fired { fired } if *fired == action.action_id => {
+                        error("requires itself to have been performed".into())
+                    }
+                    Condition::Fired { fired } if !actions.contains(fired) => {
+                        error(format!("requires action {} which does not exist", fired))
+                    }
+                    Condition::Flag { flag } if !flags.contains(flag.as_str()) => {
+                        error(format!("requires flag `{}` which no room raises", flag))
+                    }
+                    _ => {}
+                }
+            }
+
+            // the other effects on actions are simulated but left out of the spawner
+            let linked = links.iter().find(|(from, _)| *from == action.action_id);
+            let mut linked = linked.map(|(_, to)| *to);
+            for effect in &action.effects {
+                // broken effects are reported already
+                let Some(target) = effect.action().filter(|_| known.effect(effect).is_none())
+                else {
+                    continue;
+                };
+                if matches!(effect, Effect::Enable { .. }) && linked == Some(target) {
+                    linked = None;
+                    continue;
+                }
+                let message = format!(
+                    "the spawner cannot encode the `{}` effect on action {}, it only links \
+                     the first `enable` of an action of the same object",
+                    effect.kind(),
+                    target
+                );
+                problem(Severity::Warning, at.clone(), message);
+            }
+            let linking: Vec<String> = links
+                .iter()
+                .filter(|(_, to)| *to == action.action_id)
+                .map(|(from, _)| from.to_string())
+                .collect();
+            if linking.len() > 1 {
+                let message = format!(
+                    "is enabled by actions {}, the spawner only records the last one as `affectedByActionId`",
+                    linking.join(", ")
+                );
+                problem(Severity::Warning, at.clone(), message);
+            }
+
+            // a broken action is not performed either, once is enough
+            if !broken && !performed.contains(&action.action_id) {
+                problem(Severity::Warning, at, "can never be performed".into());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_missing_references() {
+        let config = crate::parse(
+            r#"
+levels:
+- levelName: test
+  rooms:
+  - roomID: 1
+    roomName: plain
+    roomDescription: ''
+    roomType: Plain
+    biomeType: Prarie
+    objectIds: [2]
+    dirObjIds: []
+    objects:
+    - objID: 2
+      type: Bale
+      material: Hay
+      objDescription: a bale
+      direction: null
+      destination: null
+      actions:
+      - actionID: 3
+        type: Burn
+        enabled: true
+        revertable: false
+        dBitText: it burns
+        dBit: true
+        requires:
+        - holding: 2
+        - fired: 4
+      - actionID: 5
+        type: Kick
+        enabled: false
+        revertable: false
+        dBitText: it rolls
+        dBit: true
+      - actionID: 6
+        type: Explode
+        enabled: true
+        revertable: false
+        dBitText: it explodes
+        dBit: true
+        effects:
+        - kind: trigger
+          action: 9
+        - kind: enable
+          action: 5
+"#,
+        );
+        let problems: Vec<String> = validate(&config).iter().map(|p| p.to_string()).collect();
+        assert_eq!(
+            problems,
+            [
+                "error: level test, object 2 `a bale`, action 3 Burn: requires holding object 2 which is not takeable",
+                "error: level test, object 2 `a bale`, action 3 Burn: requires action 4 which does not exist",
+                "error: level test, object 2 `a bale`, action 6 Explode: affects action 9 which does not exist",
+            ]
+        );
+    }
+
+    #[test]
+    fn warns_about_effects_the_spawner_cannot_encode() {
+        let config = crate::parse(
+            r#"
+levels:
+- levelName: test
+  rooms:
+  - roomID: 1
+    roomName: plain
+    roomDescription: ''
+    roomType: Plain
+    biomeType: Prarie
+    objectIds: [2]
+    dirObjIds: []
+    objects:
+    - objID: 2
+      type: Bale
+      material: Hay
+      objDescription: a bale
+      direction: null
+      destination: null
+      actions:
+      - actionID: 3
+        type: Burn
+        enabled: true
+        revertable: false
+        dBitText: it burns
+        dBit: true
+        effects:
+        - kind: disable
+          action: 3
+        - kind: enable
+          action: 5
+        - kind: enable
+          action: 4
+      - actionID: 4
+        type: Kick
+        enabled: true
+        revertable: false
+        dBitText: it rolls
+        dBit: true
+        effects:
+        - kind: enable
+          action: 5
+      - actionID: 5
+        type: Explode
+        enabled: true
+        revertable: false
+        dBitText: it explodes
+        dBit: true
+"#,
+        );
+        let problems: Vec<String> = validate(&config).iter().map(|p| p.to_string()).collect();
+        assert_eq!(
+            problems,
+            [
+                "warning: level test, object 2 `a bale`, action 3 Burn: the spawner cannot encode the `disable` effect on action 3, it only links the first `enable` of an action of the same object",
+                "warning: level test, object 2 `a bale`, action 3 Burn: the spawner cannot encode the `enable` effect on action 4, it only links the first `enable` of an action of the same object",
+                "warning: level test, object 2 `a bale`, action 5 Explode: is enabled by actions 3, 4, the spawner only records the last one as `affectedByActionId`",
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_duplicate_ids() {
+        let config = crate::parse(
+            r#"
+levels:
+- levelName: test
+  rooms:
+  - roomID: 1
+    roomName: plain
+    roomDescription: ''
+    roomType: Plain
+    biomeType: Prarie
+    objectIds: []
+    dirObjIds: []
+  - roomID: 1
+    roomName: barn
+    roomDescription: ''
+    roomType: Barn
+    biomeType: Prarie
+    objectIds: []
+    dirObjIds: []
+"#,
+        );
+        let problems: Vec<String> = validate(&config).iter().map(|p| p.to_string()).collect();
+        assert_eq!(problems, ["error: level test: room ID 1 is used 2 times"]);
+    }
+}